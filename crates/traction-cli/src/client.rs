@@ -0,0 +1,113 @@
+//! RPC client and transaction signer.
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::create_associated_token_account;
+use traction_client::accounts::try_deserialize;
+
+/// Resolves a cluster moniker to its RPC URL.
+fn resolve_url(url: &str) -> &str {
+    match url {
+        "localnet" | "l" => "http://localhost:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+}
+
+/// Expands a leading `~` to the home directory.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+/// Sends transactions to a cluster, paid for and signed by a keypair.
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Client {
+    pub fn new(url: &str, keypair_path: &str) -> Result<Self> {
+        let keypair_path = expand_home(keypair_path);
+        let payer = read_keypair_file(&keypair_path)
+            .map_err(|err| anyhow!("could not read keypair {}: {}", keypair_path, err))?;
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(
+                resolve_url(url).to_string(),
+                CommitmentConfig::confirmed(),
+            ),
+            payer,
+        })
+    }
+
+    /// Signs and sends a transaction paid for by the [Self::payer].
+    pub fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let recent_blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// Fetches an account, returning [None] if it does not exist.
+    pub fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value)
+    }
+
+    /// Fetches and deserializes a Traction account.
+    pub fn get_anchor_account<T: anchor_lang::AccountDeserialize>(
+        &self,
+        address: &Pubkey,
+    ) -> Result<T> {
+        let account = self
+            .get_account(address)?
+            .with_context(|| format!("account {} not found", address))?;
+        try_deserialize(&account.data).with_context(|| format!("invalid account {}", address))
+    }
+
+    /// Fetches and unpacks an SPL Token account or mint.
+    pub fn get_packed_account<T: Pack + IsInitialized>(&self, address: &Pubkey) -> Result<T> {
+        let account = self
+            .get_account(address)?
+            .with_context(|| format!("account {} not found", address))?;
+        T::unpack(&account.data).with_context(|| format!("invalid account {}", address))
+    }
+
+    /// Returns an instruction creating the associated token account of `owner`
+    /// for `mint`, or [None] if it already exists.
+    pub fn create_ata_if_missing(
+        &self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<Option<Instruction>> {
+        let address = spl_associated_token_account::get_associated_token_address(owner, mint);
+        Ok(match self.get_account(&address)? {
+            Some(_) => None,
+            None => Some(create_associated_token_account(
+                &self.payer.pubkey(),
+                owner,
+                mint,
+            )),
+        })
+    }
+}
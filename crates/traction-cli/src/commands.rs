@@ -0,0 +1,241 @@
+//! Subcommands.
+
+use anyhow::{Context, Result};
+use solana_sdk::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use traction_client::{
+    instructions::{self, NewContractArgs},
+    pda::*,
+    traction::{OptionMarket, OptionsContract},
+    ContractKey,
+};
+
+use crate::client::Client;
+
+/// Options of [new_contract].
+pub struct NewContractOpts {
+    pub underlying_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub strike: u64,
+    pub expiry_ts: i64,
+    pub is_put: bool,
+    pub exercise_window_secs: u32,
+    pub creator_fee_kbps: u64,
+}
+
+/// Creates a mint with the given authority, also used as the freeze authority.
+fn create_mint(client: &Client, mint: &Keypair, authority: &Pubkey, decimals: u8) -> Result<()> {
+    let lamports = client
+        .rpc
+        .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+    let ixs = [
+        system_instruction::create_account(
+            &client.payer.pubkey(),
+            &mint.pubkey(),
+            lamports,
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint(
+            &spl_token::ID,
+            &mint.pubkey(),
+            authority,
+            Some(authority),
+            decimals,
+        )?,
+    ];
+    client.send(&ixs, &[mint])?;
+    Ok(())
+}
+
+/// Creates an options contract, its mints, the market of its pair if needed,
+/// and the token accounts of its writer crate and fee recipients.
+pub fn new_contract(client: &Client, opts: NewContractOpts) -> Result<()> {
+    let key = ContractKey {
+        underlying_mint: opts.underlying_mint,
+        quote_mint: opts.quote_mint,
+        strike: opts.strike,
+        expiry_ts: opts.expiry_ts,
+        is_put: opts.is_put,
+        exercise_window_secs: opts.exercise_window_secs,
+        creator_fee_kbps: opts.creator_fee_kbps,
+        // the payer receives the creator fee, if any
+        creator_fee_to: if opts.creator_fee_kbps > 0 {
+            client.payer.pubkey()
+        } else {
+            Pubkey::default()
+        },
+        ..Default::default()
+    };
+    let (contract, _) = key.find_address();
+    let underlying: spl_token::state::Mint = client.get_packed_account(&key.underlying_mint)?;
+
+    // options and writer tokens have the decimals of the underlying
+    let option_mint = Keypair::new();
+    let writer_mint = Keypair::new();
+    let (writer_crate, _) = find_writer_crate_address(&writer_mint.pubkey());
+    create_mint(client, &option_mint, &contract, underlying.decimals)?;
+    create_mint(client, &writer_mint, &writer_crate, underlying.decimals)?;
+
+    let payer = client.payer.pubkey();
+    let mut ixs = vec![];
+    let (market, _) = find_option_market_address(&key.underlying_mint, &key.quote_mint);
+    let series_index = match client.get_account(&market)? {
+        Some(_) => {
+            client
+                .get_anchor_account::<OptionMarket>(&market)?
+                .num_series
+        }
+        None => {
+            ixs.push(instructions::new_option_market(
+                payer,
+                key.underlying_mint,
+                key.quote_mint,
+            ));
+            0
+        }
+    };
+    ixs.push(instructions::new_contract(&NewContractArgs {
+        key,
+        writer_mint: writer_mint.pubkey(),
+        option_mint: option_mint.pubkey(),
+        series_index,
+        creator: payer,
+        payer,
+    }));
+    client
+        .send(&ixs, &[])
+        .context("could not create the contract")?;
+
+    let mut ixs = vec![];
+    let mut owners = vec![
+        (writer_crate, key.collateral_mint()),
+        (writer_crate, key.exercise_mint()),
+        (traction_client::traction::FEE_OWNER, key.exercise_mint()),
+    ];
+    // exercises fail unless the creator fee can be paid
+    if key.creator_fee_kbps > 0 {
+        owners.push((key.creator_fee_to, key.exercise_mint()));
+    }
+    for (owner, mint) in owners {
+        ixs.extend(client.create_ata_if_missing(&owner, &mint)?);
+    }
+    if !ixs.is_empty() {
+        client.send(&ixs, &[])?;
+    }
+
+    println!("{}", contract);
+    Ok(())
+}
+
+/// Writes options, creating the writer's option and writer token accounts if needed.
+pub fn write(client: &Client, contract_key: &Pubkey, amount: u64) -> Result<()> {
+    let contract: OptionsContract = client.get_anchor_account(contract_key)?;
+    let writer = client.payer.pubkey();
+    let mut ixs = vec![];
+    ixs.extend(client.create_ata_if_missing(&writer, &contract.option_mint)?);
+    ixs.extend(client.create_ata_if_missing(&writer, &contract.writer_mint)?);
+    ixs.push(instructions::option_write(
+        *contract_key,
+        &contract,
+        writer,
+        amount,
+    ));
+    println!("{}", client.send(&ixs, &[])?);
+    Ok(())
+}
+
+/// Exercises options, creating the exerciser's collateral token account if needed.
+pub fn exercise(client: &Client, contract_key: &Pubkey, amount: u64) -> Result<()> {
+    let contract: OptionsContract = client.get_anchor_account(contract_key)?;
+    let exerciser = client.payer.pubkey();
+    let mut ixs = vec![];
+    ixs.extend(client.create_ata_if_missing(&exerciser, &contract.collateral_mint())?);
+    ixs.push(instructions::option_exercise(
+        *contract_key,
+        &contract,
+        exerciser,
+        amount,
+    ));
+    println!("{}", client.send(&ixs, &[])?);
+    Ok(())
+}
+
+/// Redeems writer tokens, creating the writer's collateral and exercise token accounts if needed.
+pub fn redeem(client: &Client, contract_key: &Pubkey, amount: u64) -> Result<()> {
+    let contract: OptionsContract = client.get_anchor_account(contract_key)?;
+    let writer = client.payer.pubkey();
+    let mut ixs = vec![];
+    ixs.extend(client.create_ata_if_missing(&writer, &contract.collateral_mint())?);
+    ixs.extend(client.create_ata_if_missing(&writer, &contract.exercise_mint())?);
+    ixs.push(instructions::option_redeem(
+        *contract_key,
+        &contract,
+        writer,
+        amount,
+    ));
+    println!("{}", client.send(&ixs, &[])?);
+    Ok(())
+}
+
+/// Prints an options contract, its crate balances, and its supplies.
+pub fn show(client: &Client, contract_key: &Pubkey) -> Result<()> {
+    let contract: OptionsContract = client.get_anchor_account(contract_key)?;
+    let token_amount = |address: &Pubkey| -> Result<u64> {
+        Ok(client
+            .get_packed_account::<spl_token::state::Account>(address)?
+            .amount)
+    };
+    let mint_supply = |address: &Pubkey| -> Result<u64> {
+        Ok(client
+            .get_packed_account::<spl_token::state::Mint>(address)?
+            .supply)
+    };
+
+    println!("contract:             {}", contract_key);
+    println!(
+        "type:                 {}",
+        if contract.is_put { "put" } else { "call" }
+    );
+    println!("underlying mint:      {}", contract.underlying_mint);
+    println!("quote mint:           {}", contract.quote_mint);
+    println!(
+        "strike:               {} per {} underlying",
+        contract.strike, contract.strike_units
+    );
+    println!("expiry:               {}", contract.expiry_ts);
+    println!("exercise window:      {}s", contract.exercise_window_secs);
+    println!("exercise schedule:    {:?}", contract.exercise_schedule);
+    println!("cash settled:         {}", contract.is_cash_settled);
+    println!("oracle:               {}", contract.oracle);
+    println!("creator:              {}", contract.creator);
+    println!("option mint:          {}", contract.option_mint);
+    println!("writer mint:          {}", contract.writer_mint);
+    println!("writer crate:         {}", contract.writer_crate);
+    println!(
+        "crate collateral:     {}",
+        token_amount(&contract.crate_collateral_tokens)?
+    );
+    println!(
+        "crate exercise:       {}",
+        token_amount(&contract.crate_exercise_tokens)?
+    );
+    println!(
+        "option supply:        {}",
+        mint_supply(&contract.option_mint)?
+    );
+    println!(
+        "writer supply:        {}",
+        mint_supply(&contract.writer_mint)?
+    );
+    println!("total written:        {}", contract.total_written);
+    println!("total exercised:      {}", contract.total_exercised);
+    println!("total redeemed:       {}", contract.total_redeemed);
+    println!("total closed:         {}", contract.total_closed);
+    println!("total settled:        {}", contract.total_settled);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Command line interface for managing Traction options contracts.
+//!
+//! Token amounts are raw amounts, in the smallest unit of the mint.
+
+mod client;
+mod commands;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::client::Client;
+
+#[derive(Parser)]
+#[clap(name = "traction", version, about)]
+struct Opts {
+    /// RPC URL of the cluster, or one of `localnet`, `devnet`, and `mainnet-beta`.
+    #[clap(short, long, default_value = "localnet")]
+    url: String,
+    /// Keypair which signs and pays for transactions.
+    #[clap(short, long, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Creates a new options contract, along with its option and writer mints.
+    NewContract {
+        /// Mint of the underlying asset.
+        #[clap(long)]
+        underlying_mint: Pubkey,
+        /// Mint of the quote asset.
+        #[clap(long)]
+        quote_mint: Pubkey,
+        /// Quote tokens required to buy one whole underlying token.
+        #[clap(long)]
+        strike: u64,
+        /// Expiry, in seconds since the Unix epoch.
+        #[clap(long)]
+        expiry_ts: i64,
+        /// Creates a put rather than a call.
+        #[clap(long)]
+        put: bool,
+        /// Number of seconds after expiry that options may still be exercised.
+        #[clap(long, default_value = "0")]
+        exercise_window_secs: u32,
+        /// Creator fee charged on exercise, in thousands of BPS.
+        #[clap(long, default_value = "0")]
+        creator_fee_kbps: u64,
+    },
+    /// Writes options, depositing their collateral.
+    Write {
+        /// The options contract.
+        contract: Pubkey,
+        /// Number of options to write.
+        amount: u64,
+    },
+    /// Exercises options.
+    Exercise {
+        /// The options contract.
+        contract: Pubkey,
+        /// Number of options to exercise.
+        amount: u64,
+    },
+    /// Redeems writer tokens after expiry.
+    Redeem {
+        /// The options contract.
+        contract: Pubkey,
+        /// Number of writer tokens to redeem.
+        amount: u64,
+    },
+    /// Prints an options contract, its crate balances, and its supplies.
+    Show {
+        /// The options contract.
+        contract: Pubkey,
+    },
+}
+
+fn main() -> Result<()> {
+    let opts = Opts::parse();
+    let client = Client::new(&opts.url, &opts.keypair)?;
+    match opts.command {
+        Command::NewContract {
+            underlying_mint,
+            quote_mint,
+            strike,
+            expiry_ts,
+            put,
+            exercise_window_secs,
+            creator_fee_kbps,
+        } => commands::new_contract(
+            &client,
+            commands::NewContractOpts {
+                underlying_mint,
+                quote_mint,
+                strike,
+                expiry_ts,
+                is_put: put,
+                exercise_window_secs,
+                creator_fee_kbps,
+            },
+        ),
+        Command::Write { contract, amount } => commands::write(&client, &contract, amount),
+        Command::Exercise { contract, amount } => commands::exercise(&client, &contract, amount),
+        Command::Redeem { contract, amount } => commands::redeem(&client, &contract, amount),
+        Command::Show { contract } => commands::show(&client, &contract),
+    }
+}
@@ -0,0 +1,52 @@
+//! Account deserialization.
+
+use anchor_lang::{
+    prelude::{ProgramError, Pubkey},
+    AccountDeserialize,
+};
+use traction::{OptionMarket, OptionSeries, OptionsContract};
+
+use crate::pda::find_option_series_address;
+
+/// Deserializes a Traction account, checking its discriminator.
+pub fn try_deserialize<T: AccountDeserialize>(data: &[u8]) -> Result<T, ProgramError> {
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes an [OptionsContract].
+pub fn try_deserialize_contract(data: &[u8]) -> Result<OptionsContract, ProgramError> {
+    try_deserialize(data)
+}
+
+/// Deserializes an [OptionSeries].
+pub fn try_deserialize_series(data: &[u8]) -> Result<OptionSeries, ProgramError> {
+    try_deserialize(data)
+}
+
+/// Addresses of every [OptionSeries] of an [OptionMarket], in order.
+///
+/// The series of closed contracts are closed along with them, so some of these may not exist.
+pub fn option_series_addresses(market_key: &Pubkey, market: &OptionMarket) -> Vec<Pubkey> {
+    (0..market.num_series)
+        .map(|index| find_option_series_address(market_key, index).0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    #[test]
+    fn test_try_deserialize() {
+        let contract = OptionsContract {
+            strike: 2_000_000,
+            ..Default::default()
+        };
+        let mut data = vec![];
+        contract.try_serialize(&mut data).unwrap();
+        assert_eq!(try_deserialize_contract(&data).unwrap().strike, 2_000_000);
+        // the discriminator of another account does not match
+        assert!(try_deserialize_series(&data).is_err());
+    }
+}
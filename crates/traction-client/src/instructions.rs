@@ -0,0 +1,614 @@
+//! Instruction builders.
+//!
+//! Builders take the [OptionsContract] being acted on and derive every other account from it.
+//! The token accounts of users are their associated token accounts, which must already exist.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::token;
+use spl_associated_token_account::create_associated_token_account;
+use traction::{OptionsContract, FEE_OWNER};
+
+use crate::pda::*;
+
+/// Builds a Traction instruction.
+fn traction_instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: traction::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Creates the [traction::ProtocolConfig], signed by the upgrade authority of the program
+/// as its admin.
+pub fn new_protocol_config(payer: Pubkey, admin: Pubkey, treasury: Pubkey) -> Instruction {
+    let (protocol_config, bump) = find_protocol_config_address();
+    traction_instruction(
+        traction::accounts::NewProtocolConfig {
+            protocol_config,
+            admin,
+            program_data: find_program_data_address().0,
+            payer,
+            system_program: system_program::ID,
+        },
+        traction::instruction::NewProtocolConfig { bump, treasury },
+    )
+}
+
+/// Sets the parameters of the [traction::ProtocolConfig], signed by its admin.
+pub fn set_protocol_params(
+    admin: Pubkey,
+    exercise_fee_kbps: u64,
+    treasury: Pubkey,
+    max_tenor_secs: u64,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::ProtocolConfigAdmin {
+            protocol_config: find_protocol_config_address().0,
+            admin,
+        },
+        traction::instruction::SetProtocolParams {
+            exercise_fee_kbps,
+            treasury,
+            max_tenor_secs,
+        },
+    )
+}
+
+/// Proposes `new_admin` as the admin of the [traction::ProtocolConfig], signed by its admin.
+pub fn transfer_admin(admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    traction_instruction(
+        traction::accounts::ProtocolConfigAdmin {
+            protocol_config: find_protocol_config_address().0,
+            admin,
+        },
+        traction::instruction::TransferAdmin { new_admin },
+    )
+}
+
+/// Accepts the admin role of the [traction::ProtocolConfig], signed by the pending admin.
+pub fn accept_admin(pending_admin: Pubkey) -> Instruction {
+    traction_instruction(
+        traction::accounts::AcceptAdmin {
+            protocol_config: find_protocol_config_address().0,
+            pending_admin,
+        },
+        traction::instruction::AcceptAdmin {},
+    )
+}
+
+/// Sweeps the exercise fees in `mint` held by the [FEE_OWNER] to `treasury_tokens`,
+/// which must be owned by the treasury.
+pub fn sweep_fees(mint: &Pubkey, treasury_tokens: Pubkey) -> Instruction {
+    traction_instruction(
+        traction::accounts::SweepFees {
+            protocol_config: find_protocol_config_address().0,
+            fee_owner: FEE_OWNER,
+            fee_tokens: get_exercise_fee_address(mint),
+            treasury_tokens,
+            token_program: token::ID,
+        },
+        traction::instruction::SweepFees {},
+    )
+}
+
+/// Creates the [traction::MarketParams] of an underlying/quote pair,
+/// signed by the admin of the [traction::ProtocolConfig].
+pub fn new_market_params(
+    payer: Pubkey,
+    admin: Pubkey,
+    underlying_mint: Pubkey,
+    quote_mint: Pubkey,
+    strike_tick: u64,
+    expiry_period_secs: i64,
+    expiry_offset_secs: i64,
+) -> Instruction {
+    let (market_params, bump) = find_market_params_address(&underlying_mint, &quote_mint);
+    traction_instruction(
+        traction::accounts::NewMarketParams {
+            protocol_config: find_protocol_config_address().0,
+            admin,
+            market_params,
+            underlying_mint,
+            quote_mint,
+            payer,
+            system_program: system_program::ID,
+        },
+        traction::instruction::NewMarketParams {
+            bump,
+            strike_tick,
+            expiry_period_secs,
+            expiry_offset_secs,
+        },
+    )
+}
+
+/// Updates the [traction::MarketParams] of an underlying/quote pair,
+/// signed by the admin of the [traction::ProtocolConfig].
+pub fn set_market_params(
+    admin: Pubkey,
+    underlying_mint: &Pubkey,
+    quote_mint: &Pubkey,
+    strike_tick: u64,
+    expiry_period_secs: i64,
+    expiry_offset_secs: i64,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::SetMarketParams {
+            protocol_config: find_protocol_config_address().0,
+            admin,
+            market_params: find_market_params_address(underlying_mint, quote_mint).0,
+        },
+        traction::instruction::SetMarketParams {
+            strike_tick,
+            expiry_period_secs,
+            expiry_offset_secs,
+        },
+    )
+}
+
+/// Creates a [traction::PriceOracle] of an underlying/quote pair at the `oracle` keypair,
+/// which must sign along with the `authority` publishing its prices.
+pub fn new_price_oracle(
+    payer: Pubkey,
+    oracle: Pubkey,
+    authority: Pubkey,
+    underlying_mint: Pubkey,
+    quote_mint: Pubkey,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::NewPriceOracle {
+            oracle,
+            authority,
+            underlying_mint,
+            quote_mint,
+            payer,
+            system_program: system_program::ID,
+        },
+        traction::instruction::NewPriceOracle {},
+    )
+}
+
+/// Publishes a price to a [traction::PriceOracle], signed by its authority.
+pub fn set_oracle_price(oracle: Pubkey, authority: Pubkey, price: u64) -> Instruction {
+    traction_instruction(
+        traction::accounts::SetOraclePrice { oracle, authority },
+        traction::instruction::SetOraclePrice { price },
+    )
+}
+
+/// Creates the [traction::OptionMarket] of an underlying/quote pair.
+pub fn new_option_market(
+    payer: Pubkey,
+    underlying_mint: Pubkey,
+    quote_mint: Pubkey,
+) -> Instruction {
+    let (market, bump) = find_option_market_address(&underlying_mint, &quote_mint);
+    traction_instruction(
+        traction::accounts::NewOptionMarket {
+            market,
+            underlying_mint,
+            quote_mint,
+            payer,
+            system_program: system_program::ID,
+        },
+        traction::instruction::NewOptionMarket { bump },
+    )
+}
+
+/// Arguments of [new_contract].
+#[derive(Clone, Copy, Debug)]
+pub struct NewContractArgs {
+    /// The parameters of the [OptionsContract].
+    pub key: ContractKey,
+    /// Mint of the writer tokens, with the decimals of the underlying and the
+    /// [find_writer_crate_address] as its mint and freeze authority.
+    pub writer_mint: Pubkey,
+    /// Mint of the options, with the decimals of the underlying and the
+    /// contract as its mint and freeze authority.
+    pub option_mint: Pubkey,
+    /// The [traction::OptionMarket::num_series] of the market when the contract is created.
+    pub series_index: u64,
+    /// [OptionsContract::creator], which must sign.
+    pub creator: Pubkey,
+    /// Payer of the new accounts, which must sign.
+    pub payer: Pubkey,
+}
+
+/// Creates an [OptionsContract].
+///
+/// The mints and the [traction::OptionMarket] of the pair must already exist.
+/// The token accounts of the writer crate are created separately by
+/// [create_contract_token_accounts].
+pub fn new_contract(args: &NewContractArgs) -> Instruction {
+    let key = &args.key;
+    let (contract, contract_bump) = key.find_address();
+    let (writer_crate, crate_bump) = find_writer_crate_address(&args.writer_mint);
+    let (market, _) = find_option_market_address(&key.underlying_mint, &key.quote_mint);
+    let (series, series_bump) = find_option_series_address(&market, args.series_index);
+    traction_instruction(
+        traction::accounts::NewContract {
+            contract,
+            underlying_mint: key.underlying_mint,
+            quote_mint: key.quote_mint,
+            writer_crate: traction::accounts::WriterCrate {
+                crate_mint: args.writer_mint,
+                crate_token: writer_crate,
+                crate_token_program: crate_token::ID,
+            },
+            option_mint: args.option_mint,
+            oracle: key.oracle,
+            protocol_config: find_protocol_config_address().0,
+            market,
+            series,
+            market_params: find_market_params_address(&key.underlying_mint, &key.quote_mint).0,
+            creator: args.creator,
+            payer: args.payer,
+            system_program: system_program::ID,
+        },
+        traction::instruction::NewContract {
+            strike: key.strike,
+            expiry_ts: key.expiry_ts,
+            is_put: key.is_put,
+            exercise_schedule: key.exercise_schedule,
+            is_cash_settled: key.is_cash_settled,
+            contract_bump,
+            crate_bump,
+            exercise_window_secs: key.exercise_window_secs,
+            creator_fee_kbps: key.creator_fee_kbps,
+            creator_fee_to: key.creator_fee_to,
+            series_bump,
+        },
+    )
+}
+
+/// Creates the collateral and exercise token accounts of the writer crate.
+///
+/// The [FEE_OWNER] account receiving exercise fees is shared by every contract
+/// with the same exercise mint, so it is created separately by [create_exercise_fee_account].
+pub fn create_contract_token_accounts(
+    payer: &Pubkey,
+    key: &ContractKey,
+    writer_mint: &Pubkey,
+) -> Vec<Instruction> {
+    let (writer_crate, _) = find_writer_crate_address(writer_mint);
+    vec![
+        create_associated_token_account(payer, &writer_crate, &key.collateral_mint()),
+        create_associated_token_account(payer, &writer_crate, &key.exercise_mint()),
+    ]
+}
+
+/// Creates the [FEE_OWNER] account receiving exercise fees in `exercise_mint`.
+///
+/// This fails if the account already exists, so it is only needed for the first
+/// contract exercised in `exercise_mint`.
+pub fn create_exercise_fee_account(payer: &Pubkey, exercise_mint: &Pubkey) -> Instruction {
+    create_associated_token_account(payer, &FEE_OWNER, exercise_mint)
+}
+
+/// Writes `write_amount` options, paying collateral from the writer's token account
+/// and sending the options and writer tokens to the writer.
+pub fn option_write(
+    contract_key: Pubkey,
+    contract: &OptionsContract,
+    writer: Pubkey,
+    write_amount: u64,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::OptionWrite {
+            writer_authority: writer,
+            contract: contract_key,
+            user_collateral_funding_tokens: get_associated_token_address(
+                &writer,
+                &contract.collateral_mint(),
+            ),
+            option_token_destination: get_associated_token_address(&writer, &contract.option_mint),
+            crate_collateral_tokens: contract.crate_collateral_tokens,
+            writer_token_destination: get_associated_token_address(&writer, &contract.writer_mint),
+            writer_crate_token: contract.writer_crate,
+            writer_mint: contract.writer_mint,
+            option_mint: contract.option_mint,
+            token_program: token::ID,
+            crate_token_program: crate_token::ID,
+        },
+        traction::instruction::OptionWrite { write_amount },
+    )
+}
+
+/// Exercises `option_amount` options held by the exerciser,
+/// sending the collateral to the exerciser.
+pub fn option_exercise(
+    contract_key: Pubkey,
+    contract: &OptionsContract,
+    exerciser: Pubkey,
+    option_amount: u64,
+) -> Instruction {
+    let exercise_mint = contract.exercise_mint();
+    let exercise_fee_destination = get_exercise_fee_address(&exercise_mint);
+    // the creator fee destination is not read when there is no creator fee
+    let creator_fee_destination = if contract.creator_fee_kbps > 0 {
+        get_associated_token_address(&contract.creator_fee_to, &exercise_mint)
+    } else {
+        exercise_fee_destination
+    };
+    traction_instruction(
+        traction::accounts::OptionExercise {
+            exerciser_authority: exerciser,
+            contract: contract_key,
+            exercise_token_source: get_associated_token_address(&exerciser, &exercise_mint),
+            option_mint: contract.option_mint,
+            option_token_source: get_associated_token_address(&exerciser, &contract.option_mint),
+            writer_crate_token: contract.writer_crate,
+            crate_collateral_tokens: contract.crate_collateral_tokens,
+            crate_exercise_tokens: contract.crate_exercise_tokens,
+            collateral_token_destination: get_associated_token_address(
+                &exerciser,
+                &contract.collateral_mint(),
+            ),
+            exercise_fee_destination,
+            protocol_config: find_protocol_config_address().0,
+            creator_fee_destination,
+            token_program: token::ID,
+            crate_token_program: crate_token::ID,
+        },
+        traction::instruction::OptionExercise { option_amount },
+    )
+}
+
+/// Redeems `writer_amount` writer tokens held by the writer for their share of the
+/// collateral and exercise tokens in the writer crate.
+pub fn option_redeem(
+    contract_key: Pubkey,
+    contract: &OptionsContract,
+    writer: Pubkey,
+    writer_amount: u64,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::OptionRedeem {
+            writer_authority: writer,
+            contract: contract_key,
+            writer_token_source: get_associated_token_address(&writer, &contract.writer_mint),
+            writer_mint: contract.writer_mint,
+            option_mint: contract.option_mint,
+            underlying_token_destination: get_associated_token_address(
+                &writer,
+                &contract.collateral_mint(),
+            ),
+            quote_token_destination: get_associated_token_address(
+                &writer,
+                &contract.exercise_mint(),
+            ),
+            writer_crate_token: contract.writer_crate,
+            crate_collateral_tokens: contract.crate_collateral_tokens,
+            crate_exercise_tokens: contract.crate_exercise_tokens,
+            token_program: token::ID,
+            crate_token_program: crate_token::ID,
+        },
+        traction::instruction::OptionRedeem { writer_amount },
+    )
+}
+
+/// Closes `close_amount` options and as many writer tokens held by the closer,
+/// sending the collateral backing them to the closer.
+pub fn option_close(
+    contract_key: Pubkey,
+    contract: &OptionsContract,
+    closer: Pubkey,
+    close_amount: u64,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::OptionClose {
+            closer_authority: closer,
+            contract: contract_key,
+            option_mint: contract.option_mint,
+            option_token_source: get_associated_token_address(&closer, &contract.option_mint),
+            writer_mint: contract.writer_mint,
+            writer_token_source: get_associated_token_address(&closer, &contract.writer_mint),
+            writer_crate_token: contract.writer_crate,
+            crate_collateral_tokens: contract.crate_collateral_tokens,
+            collateral_token_destination: get_associated_token_address(
+                &closer,
+                &contract.collateral_mint(),
+            ),
+            token_program: token::ID,
+            crate_token_program: crate_token::ID,
+        },
+        traction::instruction::OptionClose { close_amount },
+    )
+}
+
+/// Records the settlement price of an expired [OptionsContract] from its oracle.
+pub fn settle_contract(contract_key: Pubkey, contract: &OptionsContract) -> Instruction {
+    traction_instruction(
+        traction::accounts::SettleContract {
+            contract: contract_key,
+            oracle: contract.oracle,
+        },
+        traction::instruction::SettleContract {},
+    )
+}
+
+/// Settles `option_amount` options held by `holder` for their intrinsic value,
+/// signed by the holder or a delegate of its options. The collateral is sent to the holder.
+pub fn option_settle(
+    contract_key: Pubkey,
+    contract: &OptionsContract,
+    settler: Pubkey,
+    holder: Pubkey,
+    option_amount: u64,
+) -> Instruction {
+    traction_instruction(
+        traction::accounts::OptionSettle {
+            settler_authority: settler,
+            contract: contract_key,
+            option_mint: contract.option_mint,
+            option_token_source: get_associated_token_address(&holder, &contract.option_mint),
+            writer_crate_token: contract.writer_crate,
+            crate_collateral_tokens: contract.crate_collateral_tokens,
+            collateral_token_destination: get_associated_token_address(
+                &holder,
+                &contract.collateral_mint(),
+            ),
+            token_program: token::ID,
+            crate_token_program: crate_token::ID,
+        },
+        traction::instruction::OptionSettle { option_amount },
+    )
+}
+
+/// Closes an expired, fully redeemed [OptionsContract] and its [traction::OptionSeries],
+/// refunding their rent to the payer of the contract.
+///
+/// Tokens left in the writer crate are sent to the associated token accounts of the payer,
+/// which must already exist.
+pub fn close_contract(contract_key: Pubkey, contract: &OptionsContract) -> Instruction {
+    traction_instruction(
+        traction::accounts::CloseContract {
+            contract: contract_key,
+            series: contract.series,
+            writer_mint: contract.writer_mint,
+            option_mint: contract.option_mint,
+            writer_crate_token: contract.writer_crate,
+            crate_collateral_tokens: contract.crate_collateral_tokens,
+            crate_exercise_tokens: contract.crate_exercise_tokens,
+            collateral_destination: get_associated_token_address(
+                &contract.payer,
+                &contract.collateral_mint(),
+            ),
+            exercise_destination: get_associated_token_address(
+                &contract.payer,
+                &contract.exercise_mint(),
+            ),
+            payer: contract.payer,
+            token_program: token::ID,
+            crate_token_program: crate_token::ID,
+        },
+        traction::instruction::CloseContract {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(is_put: bool) -> (Pubkey, OptionsContract) {
+        let key = ContractKey {
+            underlying_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            strike: 2_000_000,
+            expiry_ts: 1_641_542_400,
+            is_put,
+            ..Default::default()
+        };
+        let writer_mint = Pubkey::new_unique();
+        let (writer_crate, _) = find_writer_crate_address(&writer_mint);
+        let data = OptionsContract {
+            underlying_mint: key.underlying_mint,
+            quote_mint: key.quote_mint,
+            strike: key.strike,
+            expiry_ts: key.expiry_ts,
+            is_put,
+            writer_mint,
+            writer_crate,
+            crate_collateral_tokens: get_associated_token_address(
+                &writer_crate,
+                &key.collateral_mint(),
+            ),
+            crate_exercise_tokens: get_associated_token_address(
+                &writer_crate,
+                &key.exercise_mint(),
+            ),
+            option_mint: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        (key.find_address().0, data)
+    }
+
+    #[test]
+    fn test_option_exercise_uses_exerciser_accounts() {
+        let (contract_key, data) = contract(true);
+        let exerciser = Pubkey::new_unique();
+        let ix = option_exercise(contract_key, &data, exerciser, 1_000);
+        assert_eq!(ix.program_id, traction::ID);
+
+        let metas = &ix.accounts;
+        assert_eq!(metas[0].pubkey, exerciser);
+        assert!(metas[0].is_signer);
+        assert_eq!(metas[1].pubkey, contract_key);
+        assert!(metas[1].is_writable);
+        // puts are exercised by paying the underlying for the quote collateral
+        assert_eq!(
+            metas[2].pubkey,
+            get_associated_token_address(&exerciser, &data.underlying_mint)
+        );
+        assert_eq!(
+            metas[8].pubkey,
+            get_associated_token_address(&exerciser, &data.quote_mint)
+        );
+        assert_eq!(
+            metas[9].pubkey,
+            get_exercise_fee_address(&data.underlying_mint)
+        );
+        assert_eq!(metas[11].pubkey, metas[9].pubkey);
+    }
+
+    #[test]
+    fn test_new_contract_derives_addresses() {
+        let (_, data) = contract(false);
+        let key = ContractKey::from_contract(&data);
+        let args = NewContractArgs {
+            key,
+            writer_mint: data.writer_mint,
+            option_mint: data.option_mint,
+            series_index: 3,
+            creator: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+        };
+        let ix = new_contract(&args);
+        let (market, _) = find_option_market_address(&key.underlying_mint, &key.quote_mint);
+        let addresses: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(addresses[0], key.find_address().0);
+        assert!(addresses.contains(&data.writer_crate));
+        assert!(addresses.contains(&market));
+        assert!(addresses.contains(&find_option_series_address(&market, 3).0));
+
+        let token_accounts = create_contract_token_accounts(&args.payer, &key, &data.writer_mint);
+        assert_eq!(token_accounts.len(), 2);
+    }
+
+    #[test]
+    fn test_contracts_on_same_mints_create_distinct_accounts() {
+        let (_, data) = contract(false);
+        let key = ContractKey::from_contract(&data);
+        let other_key = ContractKey {
+            strike: key.strike * 2,
+            ..key
+        };
+        let payer = Pubkey::new_unique();
+
+        // the associated token account is the second account of its creation
+        let mut created: Vec<Pubkey> =
+            [(&key, data.writer_mint), (&other_key, Pubkey::new_unique())]
+                .iter()
+                .flat_map(|(key, writer_mint)| {
+                    create_contract_token_accounts(&payer, key, writer_mint)
+                })
+                .map(|ix| ix.accounts[1].pubkey)
+                .collect();
+        assert_eq!(created.len(), 4);
+        created.sort();
+        created.dedup();
+        assert_eq!(created.len(), 4);
+
+        let fee_account = create_exercise_fee_account(&payer, &key.exercise_mint());
+        assert_eq!(
+            fee_account.accounts[1].pubkey,
+            get_exercise_fee_address(&key.exercise_mint())
+        );
+        assert!(!created.contains(&fee_account.accounts[1].pubkey));
+    }
+}
@@ -0,0 +1,14 @@
+//! Rust client for building [traction] instructions.
+//!
+//! - [pda] derives the addresses of every Traction account, including the writer crate
+//!   and its token accounts.
+//! - [instructions] builds instructions from the [traction::OptionsContract] being acted on,
+//!   deriving the associated token accounts of the user.
+//! - [accounts] deserializes Traction accounts.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+
+pub use pda::ContractKey;
+pub use traction;
@@ -0,0 +1,200 @@
+//! Program derived addresses.
+
+use anchor_lang::{prelude::Pubkey, solana_program::bpf_loader_upgradeable};
+use traction::{ExerciseSchedule, OptionsContract, FEE_OWNER};
+
+/// The parameters which uniquely define an [OptionsContract].
+///
+/// These are the seeds of the contract address, as in `gen_contract_signer_seeds!`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContractKey {
+    /// [OptionsContract::underlying_mint].
+    pub underlying_mint: Pubkey,
+    /// [OptionsContract::quote_mint].
+    pub quote_mint: Pubkey,
+    /// [OptionsContract::strike].
+    pub strike: u64,
+    /// [OptionsContract::expiry_ts].
+    pub expiry_ts: i64,
+    /// [OptionsContract::is_put].
+    pub is_put: bool,
+    /// [OptionsContract::exercise_schedule].
+    pub exercise_schedule: ExerciseSchedule,
+    /// [OptionsContract::exercise_window_secs].
+    pub exercise_window_secs: u32,
+    /// [OptionsContract::is_cash_settled].
+    pub is_cash_settled: bool,
+    /// [OptionsContract::oracle], or [Pubkey::default] if none.
+    pub oracle: Pubkey,
+    /// [OptionsContract::creator_fee_kbps].
+    pub creator_fee_kbps: u64,
+    /// [OptionsContract::creator_fee_to], or [Pubkey::default] if there is no creator fee.
+    pub creator_fee_to: Pubkey,
+}
+
+impl ContractKey {
+    /// The key of an existing [OptionsContract].
+    pub fn from_contract(contract: &OptionsContract) -> Self {
+        Self {
+            underlying_mint: contract.underlying_mint,
+            quote_mint: contract.quote_mint,
+            strike: contract.strike,
+            expiry_ts: contract.expiry_ts,
+            is_put: contract.is_put,
+            exercise_schedule: contract.exercise_schedule,
+            exercise_window_secs: contract.exercise_window_secs,
+            is_cash_settled: contract.is_cash_settled,
+            oracle: contract.oracle,
+            creator_fee_kbps: contract.creator_fee_kbps,
+            creator_fee_to: contract.creator_fee_to,
+        }
+    }
+
+    /// Finds the address and bump of the [OptionsContract].
+    pub fn find_address(&self) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"OptionsContract",
+                self.underlying_mint.as_ref(),
+                self.quote_mint.as_ref(),
+                &self.strike.to_le_bytes(),
+                &self.expiry_ts.to_le_bytes(),
+                &[self.is_put as u8],
+                &self.exercise_schedule.to_seed(),
+                &self.exercise_window_secs.to_le_bytes(),
+                &[self.is_cash_settled as u8],
+                self.oracle.as_ref(),
+                &self.creator_fee_kbps.to_le_bytes(),
+                self.creator_fee_to.as_ref(),
+            ],
+            &traction::ID,
+        )
+    }
+
+    /// Mint of the collateral, as in [OptionsContract::collateral_mint].
+    pub fn collateral_mint(&self) -> Pubkey {
+        if self.is_put {
+            self.quote_mint
+        } else {
+            self.underlying_mint
+        }
+    }
+
+    /// Mint of the exercise, as in [OptionsContract::exercise_mint].
+    pub fn exercise_mint(&self) -> Pubkey {
+        if self.is_put {
+            self.underlying_mint
+        } else {
+            self.quote_mint
+        }
+    }
+}
+
+/// Finds the [bpf_loader_upgradeable] program data address of the Traction program,
+/// whose upgrade authority may create the [traction::ProtocolConfig].
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[traction::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// Finds the address of the [traction::ProtocolConfig].
+pub fn find_protocol_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"ProtocolConfig"], &traction::ID)
+}
+
+/// Finds the address of the [traction::OptionMarket] of an underlying/quote pair.
+pub fn find_option_market_address(underlying_mint: &Pubkey, quote_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"OptionMarket",
+            underlying_mint.as_ref(),
+            quote_mint.as_ref(),
+        ],
+        &traction::ID,
+    )
+}
+
+/// Finds the address of the [traction::OptionSeries] at `index` of an [traction::OptionMarket].
+pub fn find_option_series_address(market: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"OptionSeries", market.as_ref(), &index.to_le_bytes()],
+        &traction::ID,
+    )
+}
+
+/// Finds the address of the [traction::MarketParams] of an underlying/quote pair.
+pub fn find_market_params_address(underlying_mint: &Pubkey, quote_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"MarketParams",
+            underlying_mint.as_ref(),
+            quote_mint.as_ref(),
+        ],
+        &traction::ID,
+    )
+}
+
+/// Finds the address of the [crate_token::CrateToken] of a writer mint.
+pub fn find_writer_crate_address(writer_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"CrateToken", writer_mint.as_ref()], &crate_token::ID)
+}
+
+/// Associated token account of `owner` for `mint`.
+pub fn get_associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(owner, mint)
+}
+
+/// The [FEE_OWNER] token account which receives exercise fees in `exercise_mint`.
+pub fn get_exercise_fee_address(exercise_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&FEE_OWNER, exercise_mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traction::{gen_contract_signer_seeds, ExerciseStyle};
+
+    #[test]
+    fn test_contract_address_matches_signer_seeds() {
+        let key = ContractKey {
+            underlying_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            strike: 2_000_000,
+            expiry_ts: 1_641_542_400,
+            is_put: true,
+            exercise_schedule: ExerciseSchedule {
+                style: ExerciseStyle::Bermudan,
+                window_secs: 3_600,
+                period_secs: 86_400,
+            },
+            exercise_window_secs: 600,
+            is_cash_settled: true,
+            oracle: Pubkey::new_unique(),
+            creator_fee_kbps: 500,
+            creator_fee_to: Pubkey::new_unique(),
+        };
+        let (address, bump) = key.find_address();
+        let contract = OptionsContract {
+            underlying_mint: key.underlying_mint,
+            quote_mint: key.quote_mint,
+            strike: key.strike,
+            expiry_ts: key.expiry_ts,
+            is_put: key.is_put,
+            exercise_schedule: key.exercise_schedule,
+            exercise_window_secs: key.exercise_window_secs,
+            is_cash_settled: key.is_cash_settled,
+            oracle: key.oracle,
+            creator_fee_kbps: key.creator_fee_kbps,
+            creator_fee_to: key.creator_fee_to,
+            bump,
+            ..Default::default()
+        };
+        let seeds: &[&[&[u8]]] = gen_contract_signer_seeds!(contract);
+        assert_eq!(
+            Pubkey::create_program_address(seeds[0], &traction::ID).unwrap(),
+            address
+        );
+        assert_eq!(ContractKey::from_contract(&contract), key);
+        assert_eq!(key.collateral_mint(), key.quote_mint);
+        assert_eq!(key.exercise_mint(), key.underlying_mint);
+    }
+}
@@ -0,0 +1,577 @@
+//! SQLite storage of [TractionEvent]s.
+//!
+//! The `events` table holds every event, keyed by the signature of its transaction
+//! and its index within it, so ingesting a transaction twice is a no-op. The
+//! `contracts` table holds the parameters of each contract, as emitted on its creation.
+//! The derived `open_interest` and `positions` tables are updated by the deltas of each
+//! newly inserted event, so they do not depend on the order transactions are ingested in.
+//!
+//! Options and writer tokens are transferable, so a [Position] tracks what a user did
+//! through the contract rather than the tokens they currently hold.
+
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row, Transaction};
+use traction_client::traction::{ExerciseSchedule, ExerciseStyle, NewContractEvent};
+
+use crate::logs::{parse_logs, TractionEvent};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    contract TEXT NOT NULL,
+    user TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    collateral_amount INTEGER NOT NULL,
+    exercise_amount INTEGER NOT NULL,
+    option_supply INTEGER,
+    writer_supply INTEGER,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_contract ON events (contract, slot);
+CREATE INDEX IF NOT EXISTS events_user ON events (user, slot);
+
+CREATE TABLE IF NOT EXISTS contracts (
+    contract TEXT PRIMARY KEY NOT NULL,
+    slot INTEGER NOT NULL,
+    creator TEXT NOT NULL,
+    underlying_mint TEXT NOT NULL,
+    quote_mint TEXT NOT NULL,
+    strike INTEGER NOT NULL,
+    strike_units INTEGER NOT NULL,
+    expiry_ts INTEGER NOT NULL,
+    is_put INTEGER NOT NULL,
+    exercise_style TEXT NOT NULL,
+    exercise_schedule_window_secs INTEGER NOT NULL,
+    exercise_schedule_period_secs INTEGER NOT NULL,
+    exercise_window_secs INTEGER NOT NULL,
+    oracle TEXT NOT NULL,
+    is_cash_settled INTEGER NOT NULL,
+    creator_fee_kbps INTEGER NOT NULL,
+    creator_fee_to TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS contracts_pair ON contracts (underlying_mint, quote_mint, expiry_ts);
+
+CREATE TABLE IF NOT EXISTS open_interest (
+    contract TEXT PRIMARY KEY NOT NULL,
+    option_supply INTEGER NOT NULL,
+    writer_supply INTEGER NOT NULL,
+    written INTEGER NOT NULL,
+    exercised INTEGER NOT NULL,
+    redeemed INTEGER NOT NULL,
+    closed INTEGER NOT NULL,
+    settled INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS positions (
+    contract TEXT NOT NULL,
+    user TEXT NOT NULL,
+    written INTEGER NOT NULL,
+    exercised INTEGER NOT NULL,
+    redeemed INTEGER NOT NULL,
+    closed INTEGER NOT NULL,
+    settled INTEGER NOT NULL,
+    collateral_deposited INTEGER NOT NULL,
+    collateral_withdrawn INTEGER NOT NULL,
+    exercise_deposited INTEGER NOT NULL,
+    exercise_withdrawn INTEGER NOT NULL,
+    PRIMARY KEY (contract, user)
+);
+";
+
+/// Parameters of an options contract, as emitted on its creation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contract {
+    /// Slot of the transaction that created the contract.
+    pub slot: u64,
+    /// The [traction::OptionsContract::creator].
+    pub creator: Pubkey,
+    /// Mint of the underlying asset.
+    pub underlying_mint: Pubkey,
+    /// Mint of the quote asset.
+    pub quote_mint: Pubkey,
+    /// The [traction::OptionsContract::strike].
+    pub strike: u64,
+    /// The [traction::OptionsContract::strike_units].
+    pub strike_units: u64,
+    /// The [traction::OptionsContract::expiry_ts].
+    pub expiry_ts: i64,
+    /// If the option is a put.
+    pub is_put: bool,
+    /// The [traction::OptionsContract::exercise_schedule].
+    pub exercise_schedule: ExerciseSchedule,
+    /// The [traction::OptionsContract::exercise_window_secs].
+    pub exercise_window_secs: u32,
+    /// The [traction::OptionsContract::oracle].
+    pub oracle: Pubkey,
+    /// If the option is settled in cash.
+    pub is_cash_settled: bool,
+    /// The [traction::OptionsContract::creator_fee_kbps].
+    pub creator_fee_kbps: u64,
+    /// The [traction::OptionsContract::creator_fee_to].
+    pub creator_fee_to: Pubkey,
+    /// Timestamp of the creation of the contract.
+    pub created_at: i64,
+}
+
+/// Open interest of an options contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenInterest {
+    /// Options outstanding.
+    pub option_supply: i64,
+    /// Writer tokens outstanding.
+    pub writer_supply: i64,
+    /// Options written.
+    pub written: i64,
+    /// Options exercised.
+    pub exercised: i64,
+    /// Writer tokens redeemed.
+    pub redeemed: i64,
+    /// Options and writer tokens closed.
+    pub closed: i64,
+    /// Options settled in cash.
+    pub settled: i64,
+}
+
+/// Activity of a user in an options contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Options written.
+    pub written: i64,
+    /// Options exercised.
+    pub exercised: i64,
+    /// Writer tokens redeemed.
+    pub redeemed: i64,
+    /// Options and writer tokens closed.
+    pub closed: i64,
+    /// Options settled in cash.
+    pub settled: i64,
+    /// Collateral tokens deposited into the crate.
+    pub collateral_deposited: i64,
+    /// Collateral tokens withdrawn from the crate.
+    pub collateral_withdrawn: i64,
+    /// Exercise tokens paid, including fees.
+    pub exercise_deposited: i64,
+    /// Exercise tokens withdrawn from the crate.
+    pub exercise_withdrawn: i64,
+}
+
+/// A row of the `events` table.
+struct EventRow {
+    kind: &'static str,
+    contract: Pubkey,
+    user: Pubkey,
+    amount: u64,
+    collateral_amount: u64,
+    exercise_amount: u64,
+    /// Supply of options after the event, if it reports it.
+    option_supply: Option<u64>,
+    /// Supply of writer tokens after the event, if it reports it.
+    writer_supply: Option<u64>,
+    timestamp: i64,
+}
+
+impl From<&TractionEvent> for EventRow {
+    fn from(event: &TractionEvent) -> Self {
+        match event {
+            TractionEvent::NewContract(e) => EventRow {
+                kind: "new_contract",
+                contract: e.contract,
+                user: e.creator,
+                amount: 0,
+                collateral_amount: 0,
+                exercise_amount: 0,
+                option_supply: None,
+                writer_supply: None,
+                timestamp: e.timestamp,
+            },
+            TractionEvent::Write(e) => EventRow {
+                kind: "write",
+                contract: e.contract,
+                user: e.writer,
+                amount: e.write_amount,
+                collateral_amount: e.collateral_amount,
+                exercise_amount: 0,
+                option_supply: Some(e.option_supply),
+                writer_supply: Some(e.writer_supply),
+                timestamp: e.timestamp,
+            },
+            TractionEvent::Exercise(e) => EventRow {
+                kind: "exercise",
+                contract: e.contract,
+                user: e.exerciser,
+                amount: e.option_amount,
+                collateral_amount: e.collateral_amount,
+                exercise_amount: e.exercise_amount,
+                option_supply: Some(e.option_supply),
+                writer_supply: None,
+                timestamp: e.timestamp,
+            },
+            TractionEvent::Redeem(e) => EventRow {
+                kind: "redeem",
+                contract: e.contract,
+                user: e.redeemer,
+                amount: e.writer_amount,
+                collateral_amount: e.collateral_amount,
+                exercise_amount: e.exercise_amount,
+                option_supply: Some(e.option_supply),
+                writer_supply: Some(e.writer_supply),
+                timestamp: e.timestamp,
+            },
+            TractionEvent::Close(e) => EventRow {
+                kind: "close",
+                contract: e.contract,
+                user: e.closer,
+                amount: e.close_amount,
+                collateral_amount: e.collateral_amount,
+                exercise_amount: 0,
+                option_supply: Some(e.option_supply),
+                writer_supply: Some(e.writer_supply),
+                timestamp: e.timestamp,
+            },
+            TractionEvent::Settle(e) => EventRow {
+                kind: "settle",
+                contract: e.contract,
+                user: e.holder,
+                amount: e.option_amount,
+                collateral_amount: e.settlement_amount,
+                exercise_amount: 0,
+                option_supply: Some(e.option_supply),
+                writer_supply: None,
+                timestamp: e.timestamp,
+            },
+        }
+    }
+}
+
+/// Change of the [Position] of the user of an event.
+fn position_delta(event: &TractionEvent) -> Position {
+    match event {
+        TractionEvent::NewContract(_) => Position::default(),
+        TractionEvent::Write(e) => Position {
+            written: e.write_amount as i64,
+            collateral_deposited: e.collateral_amount as i64,
+            ..Default::default()
+        },
+        TractionEvent::Exercise(e) => Position {
+            exercised: e.option_amount as i64,
+            collateral_withdrawn: e.collateral_amount as i64,
+            exercise_deposited: e.exercise_amount as i64,
+            ..Default::default()
+        },
+        TractionEvent::Redeem(e) => Position {
+            redeemed: e.writer_amount as i64,
+            collateral_withdrawn: e.collateral_amount as i64,
+            exercise_withdrawn: e.exercise_amount as i64,
+            ..Default::default()
+        },
+        TractionEvent::Close(e) => Position {
+            closed: e.close_amount as i64,
+            collateral_withdrawn: e.collateral_amount as i64,
+            ..Default::default()
+        },
+        TractionEvent::Settle(e) => Position {
+            settled: e.option_amount as i64,
+            collateral_withdrawn: e.settlement_amount as i64,
+            ..Default::default()
+        },
+    }
+}
+
+/// Indexes the events of a Traction program into a SQLite database.
+pub struct Indexer {
+    conn: Connection,
+    program_id: Pubkey,
+}
+
+impl Indexer {
+    /// Opens the database at `path`, creating its tables if needed.
+    pub fn open(path: impl AsRef<Path>, program_id: Pubkey) -> rusqlite::Result<Self> {
+        Self::new(Connection::open(path)?, program_id)
+    }
+
+    /// Opens an in-memory database.
+    pub fn open_in_memory(program_id: Pubkey) -> rusqlite::Result<Self> {
+        Self::new(Connection::open_in_memory()?, program_id)
+    }
+
+    fn new(conn: Connection, program_id: Pubkey) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn, program_id })
+    }
+
+    /// The underlying connection, for custom queries.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Ingests the log messages of a successful transaction, returning the number of
+    /// events newly indexed.
+    pub fn ingest_transaction(
+        &mut self,
+        slot: u64,
+        signature: &str,
+        logs: &[String],
+    ) -> rusqlite::Result<usize> {
+        let events = parse_logs(&self.program_id, logs);
+        let tx = self.conn.transaction()?;
+        let mut inserted = 0;
+        for (index, event) in events.iter().enumerate() {
+            if insert_event(&tx, slot, signature, index, event)? {
+                inserted += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// The parameters of an options contract, or [None] if its creation was not indexed.
+    pub fn contract(&self, contract: &Pubkey) -> rusqlite::Result<Option<Contract>> {
+        self.conn
+            .query_row(
+                "SELECT slot, creator, underlying_mint, quote_mint, strike, strike_units,
+                    expiry_ts, is_put, exercise_style, exercise_schedule_window_secs,
+                    exercise_schedule_period_secs, exercise_window_secs, oracle,
+                    is_cash_settled, creator_fee_kbps, creator_fee_to, created_at
+                FROM contracts WHERE contract = ?1",
+                params![contract.to_string()],
+                |row| {
+                    Ok(Contract {
+                        slot: row.get::<_, i64>(0)? as u64,
+                        creator: get_pubkey(row, 1)?,
+                        underlying_mint: get_pubkey(row, 2)?,
+                        quote_mint: get_pubkey(row, 3)?,
+                        strike: row.get::<_, i64>(4)? as u64,
+                        strike_units: row.get::<_, i64>(5)? as u64,
+                        expiry_ts: row.get(6)?,
+                        is_put: row.get(7)?,
+                        exercise_schedule: ExerciseSchedule {
+                            style: get_exercise_style(row, 8)?,
+                            window_secs: row.get(9)?,
+                            period_secs: row.get(10)?,
+                        },
+                        exercise_window_secs: row.get(11)?,
+                        oracle: get_pubkey(row, 12)?,
+                        is_cash_settled: row.get(13)?,
+                        creator_fee_kbps: row.get::<_, i64>(14)? as u64,
+                        creator_fee_to: get_pubkey(row, 15)?,
+                        created_at: row.get(16)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// The [OpenInterest] of an options contract, or [None] if it has no events.
+    pub fn open_interest(&self, contract: &Pubkey) -> rusqlite::Result<Option<OpenInterest>> {
+        self.conn
+            .query_row(
+                "SELECT option_supply, writer_supply, written, exercised, redeemed, closed, settled
+                FROM open_interest WHERE contract = ?1",
+                params![contract.to_string()],
+                |row| {
+                    Ok(OpenInterest {
+                        option_supply: row.get(0)?,
+                        writer_supply: row.get(1)?,
+                        written: row.get(2)?,
+                        exercised: row.get(3)?,
+                        redeemed: row.get(4)?,
+                        closed: row.get(5)?,
+                        settled: row.get(6)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// The [Position] of a user in an options contract, or [None] if they have no events.
+    pub fn position(&self, contract: &Pubkey, user: &Pubkey) -> rusqlite::Result<Option<Position>> {
+        self.conn
+            .query_row(
+                "SELECT written, exercised, redeemed, closed, settled,
+                    collateral_deposited, collateral_withdrawn, exercise_deposited, exercise_withdrawn
+                FROM positions WHERE contract = ?1 AND user = ?2",
+                params![contract.to_string(), user.to_string()],
+                |row| {
+                    Ok(Position {
+                        written: row.get(0)?,
+                        exercised: row.get(1)?,
+                        redeemed: row.get(2)?,
+                        closed: row.get(3)?,
+                        settled: row.get(4)?,
+                        collateral_deposited: row.get(5)?,
+                        collateral_withdrawn: row.get(6)?,
+                        exercise_deposited: row.get(7)?,
+                        exercise_withdrawn: row.get(8)?,
+                    })
+                },
+            )
+            .optional()
+    }
+}
+
+fn exercise_style_name(style: ExerciseStyle) -> &'static str {
+    match style {
+        ExerciseStyle::American => "american",
+        ExerciseStyle::European => "european",
+        ExerciseStyle::Bermudan => "bermudan",
+    }
+}
+
+fn get_exercise_style(row: &Row, index: usize) -> rusqlite::Result<ExerciseStyle> {
+    let name: String = row.get(index)?;
+    [
+        ExerciseStyle::American,
+        ExerciseStyle::European,
+        ExerciseStyle::Bermudan,
+    ]
+    .into_iter()
+    .find(|style| exercise_style_name(*style) == name)
+    .ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            Type::Text,
+            format!("invalid exercise style {}", name).into(),
+        )
+    })
+}
+
+fn get_pubkey(row: &Row, index: usize) -> rusqlite::Result<Pubkey> {
+    row.get::<_, String>(index)?
+        .parse()
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(err)))
+}
+
+/// Inserts the parameters of a newly created contract.
+fn insert_contract(tx: &Transaction, slot: u64, e: &NewContractEvent) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT OR IGNORE INTO contracts (
+            contract, slot, creator, underlying_mint, quote_mint, strike, strike_units,
+            expiry_ts, is_put, exercise_style, exercise_schedule_window_secs,
+            exercise_schedule_period_secs, exercise_window_secs, oracle,
+            is_cash_settled, creator_fee_kbps, creator_fee_to, created_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        params![
+            e.contract.to_string(),
+            slot as i64,
+            e.creator.to_string(),
+            e.underlying_mint.to_string(),
+            e.quote_mint.to_string(),
+            e.strike as i64,
+            e.strike_units as i64,
+            e.expiry_ts,
+            e.is_put,
+            exercise_style_name(e.exercise_schedule.style),
+            e.exercise_schedule.window_secs,
+            e.exercise_schedule.period_secs,
+            e.exercise_window_secs,
+            e.oracle.to_string(),
+            e.is_cash_settled,
+            e.creator_fee_kbps as i64,
+            e.creator_fee_to.to_string(),
+            e.timestamp,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Inserts an event and applies it to the derived tables, returning false if it
+/// was already indexed.
+fn insert_event(
+    tx: &Transaction,
+    slot: u64,
+    signature: &str,
+    index: usize,
+    event: &TractionEvent,
+) -> rusqlite::Result<bool> {
+    let row = EventRow::from(event);
+    let contract = row.contract.to_string();
+    let inserted = tx.execute(
+        "INSERT OR IGNORE INTO events (
+            signature, event_index, slot, kind, contract, user, amount,
+            collateral_amount, exercise_amount, option_supply, writer_supply, timestamp
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            signature,
+            index as i64,
+            slot as i64,
+            row.kind,
+            contract,
+            row.user.to_string(),
+            row.amount as i64,
+            row.collateral_amount as i64,
+            row.exercise_amount as i64,
+            row.option_supply.map(|supply| supply as i64),
+            row.writer_supply.map(|supply| supply as i64),
+            row.timestamp,
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+    if let TractionEvent::NewContract(e) = event {
+        insert_contract(tx, slot, e)?;
+        return Ok(true);
+    }
+
+    let d = position_delta(event);
+    tx.execute(
+        "INSERT INTO open_interest (
+            contract, option_supply, writer_supply, written, exercised, redeemed, closed, settled
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        ON CONFLICT (contract) DO UPDATE SET
+            option_supply = option_supply + excluded.option_supply,
+            writer_supply = writer_supply + excluded.writer_supply,
+            written = written + excluded.written,
+            exercised = exercised + excluded.exercised,
+            redeemed = redeemed + excluded.redeemed,
+            closed = closed + excluded.closed,
+            settled = settled + excluded.settled",
+        params![
+            contract,
+            d.written - d.exercised - d.closed - d.settled,
+            d.written - d.redeemed - d.closed,
+            d.written,
+            d.exercised,
+            d.redeemed,
+            d.closed,
+            d.settled,
+        ],
+    )?;
+    tx.execute(
+        "INSERT INTO positions (
+            contract, user, written, exercised, redeemed, closed, settled,
+            collateral_deposited, collateral_withdrawn, exercise_deposited, exercise_withdrawn
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+        ON CONFLICT (contract, user) DO UPDATE SET
+            written = written + excluded.written,
+            exercised = exercised + excluded.exercised,
+            redeemed = redeemed + excluded.redeemed,
+            closed = closed + excluded.closed,
+            settled = settled + excluded.settled,
+            collateral_deposited = collateral_deposited + excluded.collateral_deposited,
+            collateral_withdrawn = collateral_withdrawn + excluded.collateral_withdrawn,
+            exercise_deposited = exercise_deposited + excluded.exercise_deposited,
+            exercise_withdrawn = exercise_withdrawn + excluded.exercise_withdrawn",
+        params![
+            contract,
+            row.user.to_string(),
+            d.written,
+            d.exercised,
+            d.redeemed,
+            d.closed,
+            d.settled,
+            d.collateral_deposited,
+            d.collateral_withdrawn,
+            d.exercise_deposited,
+            d.exercise_withdrawn,
+        ],
+    )?;
+    Ok(true)
+}
@@ -0,0 +1,15 @@
+//! Indexes [traction] events into SQLite.
+//!
+//! - [logs] decodes [TractionEvent]s from the log messages of a transaction.
+//! - [db] stores the events of each transaction along with its slot and signature,
+//!   the parameters of each [traction::OptionsContract], and maintains the open interest
+//!   of each contract and the position of each user in it.
+//!
+//! The indexer does not fetch transactions itself: it is fed the log messages of
+//! successful transactions, for example from `getTransaction` or a log subscription.
+
+pub mod db;
+pub mod logs;
+
+pub use db::{Contract, Indexer, OpenInterest, Position};
+pub use logs::{parse_logs, TractionEvent};
@@ -0,0 +1,140 @@
+//! Decoding of [traction] events from transaction logs.
+//!
+//! Anchor emits an event as a `Program log: ` message containing the base64 of its
+//! discriminator followed by its Borsh serialization. Only messages logged while
+//! the Traction program is at the top of the invocation stack are decoded, so other
+//! programs cannot forge events.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Discriminator};
+use traction_client::traction::{
+    NewContractEvent, OptionCloseEvent, OptionExerciseEvent, OptionRedeemEvent, OptionSettleEvent,
+    OptionWriteEvent,
+};
+
+const PROGRAM_LOG: &str = "Program log: ";
+
+/// An event which creates an options contract or changes its open interest.
+pub enum TractionEvent {
+    NewContract(NewContractEvent),
+    Write(OptionWriteEvent),
+    Exercise(OptionExerciseEvent),
+    Redeem(OptionRedeemEvent),
+    Close(OptionCloseEvent),
+    Settle(OptionSettleEvent),
+}
+
+impl TractionEvent {
+    /// Decodes an event from its discriminator and data, returning [None] if it
+    /// is not a [TractionEvent].
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        try_decode(data)
+            .map(TractionEvent::NewContract)
+            .or_else(|| try_decode(data).map(TractionEvent::Write))
+            .or_else(|| try_decode(data).map(TractionEvent::Exercise))
+            .or_else(|| try_decode(data).map(TractionEvent::Redeem))
+            .or_else(|| try_decode(data).map(TractionEvent::Close))
+            .or_else(|| try_decode(data).map(TractionEvent::Settle))
+    }
+}
+
+fn try_decode<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    if data.len() < 8 || data[..8] != T::discriminator() {
+        return None;
+    }
+    T::deserialize(&mut &data[8..]).ok()
+}
+
+/// Parses the [TractionEvent]s emitted by `program_id` from the log messages of a
+/// transaction, in the order they were emitted.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> Vec<TractionEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = vec![];
+    let mut events = vec![];
+    for log in logs {
+        if let Some(message) = log.strip_prefix(PROGRAM_LOG) {
+            if stack.last() == Some(&program_id.as_str()) {
+                events.extend(
+                    base64::decode(message)
+                        .ok()
+                        .and_then(|data| TractionEvent::decode(&data)),
+                );
+            }
+            continue;
+        }
+        let mut words = log.split_whitespace();
+        if words.next() != Some("Program") {
+            continue;
+        }
+        match (words.next(), words.next()) {
+            (Some(id), Some("invoke")) => stack.push(id),
+            (Some(_), Some("success" | "failed:")) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn write_event(write_amount: u64) -> OptionWriteEvent {
+        OptionWriteEvent {
+            contract: Pubkey::new_unique(),
+            writer: Pubkey::new_unique(),
+            write_amount,
+            collateral_amount: write_amount,
+            option_supply: write_amount,
+            writer_supply: write_amount,
+            timestamp: 0,
+        }
+    }
+
+    fn log_event(event: &impl Event) -> String {
+        format!("{}{}", PROGRAM_LOG, base64::encode(event.data()))
+    }
+
+    #[test]
+    fn test_parse_logs() {
+        let program_id = traction_client::traction::ID;
+        let token_program = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: OptionWrite".to_string(),
+            format!("Program {} invoke [2]", token_program),
+            // an event logged by another program is ignored
+            log_event(&write_event(1)),
+            format!("Program {} success", token_program),
+            log_event(&write_event(2)),
+            format!(
+                "Program {} consumed 20000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+            format!("Program {} invoke [1]", other_program),
+            log_event(&write_event(3)),
+            format!("Program {} success", other_program),
+        ];
+        let events = parse_logs(&program_id, &logs);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            TractionEvent::Write(event) => assert_eq!(event.write_amount, 2),
+            _ => panic!("expected a write event"),
+        }
+    }
+
+    #[test]
+    fn test_decode_ignores_other_events() {
+        let event = traction_client::traction::CloseContractEvent {
+            contract: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            timestamp: 0,
+        };
+        assert!(TractionEvent::decode(&event.data()).is_none());
+        assert!(TractionEvent::decode(&[]).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::Pubkey;
+use serde::{Deserialize, Deserializer};
+use traction_client::traction::ExerciseSchedule;
+use traction_indexer::{Indexer, OpenInterest, Position};
+
+/// Transactions of the Traction program and the accounts they involve.
+///
+/// The transactions are recorded by running the program in-process with
+/// `solana-program-test`: see `programs/traction/tests/indexer_fixture.rs`.
+#[derive(Deserialize)]
+struct Fixture {
+    #[serde(deserialize_with = "pubkey")]
+    contract: Pubkey,
+    /// Creates the contract, along with the market of its pair.
+    #[serde(deserialize_with = "pubkey")]
+    creator: Pubkey,
+    #[serde(deserialize_with = "pubkey")]
+    underlying_mint: Pubkey,
+    #[serde(deserialize_with = "pubkey")]
+    quote_mint: Pubkey,
+    /// Writes 10 options, transfers 4 to Bob, and redeems her writer tokens after expiry.
+    #[serde(deserialize_with = "pubkey")]
+    alice: Pubkey,
+    /// Exercises the 4 options received from Alice.
+    #[serde(deserialize_with = "pubkey")]
+    bob: Pubkey,
+    /// Writes 5 options and closes 2 of them.
+    #[serde(deserialize_with = "pubkey")]
+    carol: Pubkey,
+    transactions: Vec<RecordedTransaction>,
+}
+
+/// A transaction along with its slot and log messages.
+#[derive(Clone, Deserialize)]
+struct RecordedTransaction {
+    slot: u64,
+    signature: String,
+    logs: Vec<String>,
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(|_| serde::de::Error::custom("invalid pubkey"))
+}
+
+fn fixture() -> Fixture {
+    serde_json::from_str(include_str!("fixtures/transactions.json")).unwrap()
+}
+
+/// The first write of the fixture, as if it had been sent to another program
+/// logging the same events.
+fn forged_transaction(fixture: &Fixture) -> RecordedTransaction {
+    let program_id = traction_client::traction::ID.to_string();
+    let other_program_id = Pubkey::new_unique().to_string();
+    let tx = fixture
+        .transactions
+        .iter()
+        .find(|tx| {
+            tx.logs
+                .iter()
+                .any(|log| log == "Program log: Instruction: OptionWrite")
+        })
+        .unwrap();
+    RecordedTransaction {
+        slot: tx.slot,
+        signature: tx.signature.clone(),
+        logs: tx
+            .logs
+            .iter()
+            .map(|log| log.replace(&program_id, &other_program_id))
+            .collect(),
+    }
+}
+
+fn ingest(indexer: &mut Indexer, txs: &[RecordedTransaction]) -> usize {
+    txs.iter()
+        .map(|tx| {
+            indexer
+                .ingest_transaction(tx.slot, &tx.signature, &tx.logs)
+                .unwrap()
+        })
+        .sum()
+}
+
+#[test]
+fn test_ingest_fixture() {
+    let fixture = fixture();
+    let mut indexer = Indexer::open_in_memory(traction_client::traction::ID).unwrap();
+    assert_eq!(ingest(&mut indexer, &fixture.transactions), 6);
+
+    let contract = indexer.contract(&fixture.contract).unwrap().unwrap();
+    assert_eq!(contract.slot, 90);
+    assert_eq!(contract.creator, fixture.creator);
+    assert_eq!(contract.underlying_mint, fixture.underlying_mint);
+    assert_eq!(contract.quote_mint, fixture.quote_mint);
+    assert_eq!(contract.strike, 2);
+    assert_eq!(contract.strike_units, 1);
+    assert_eq!(contract.expiry_ts, contract.created_at + 1_000);
+    assert!(!contract.is_put);
+    assert_eq!(contract.exercise_schedule, ExerciseSchedule::default());
+    assert_eq!(contract.exercise_window_secs, 0);
+    assert_eq!(contract.oracle, Pubkey::default());
+    assert!(!contract.is_cash_settled);
+    assert_eq!(contract.creator_fee_kbps, 0);
+    assert_eq!(contract.creator_fee_to, Pubkey::default());
+    assert!(indexer.contract(&Pubkey::new_unique()).unwrap().is_none());
+
+    assert_eq!(
+        indexer.open_interest(&fixture.contract).unwrap().unwrap(),
+        OpenInterest {
+            option_supply: 9,
+            writer_supply: 3,
+            written: 15,
+            exercised: 4,
+            redeemed: 10,
+            closed: 2,
+            settled: 0,
+        }
+    );
+    assert_eq!(
+        indexer
+            .position(&fixture.contract, &fixture.alice)
+            .unwrap()
+            .unwrap(),
+        Position {
+            written: 10,
+            redeemed: 10,
+            collateral_deposited: 10,
+            collateral_withdrawn: 6,
+            exercise_withdrawn: 6,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        indexer
+            .position(&fixture.contract, &fixture.bob)
+            .unwrap()
+            .unwrap(),
+        Position {
+            exercised: 4,
+            collateral_withdrawn: 4,
+            exercise_deposited: 8,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        indexer
+            .position(&fixture.contract, &fixture.carol)
+            .unwrap()
+            .unwrap(),
+        Position {
+            written: 5,
+            closed: 2,
+            collateral_deposited: 5,
+            collateral_withdrawn: 2,
+            ..Default::default()
+        }
+    );
+    assert!(indexer
+        .position(&fixture.contract, &Pubkey::new_unique())
+        .unwrap()
+        .is_none());
+
+    let (slot, kind, option_supply, writer_supply): (i64, String, Option<i64>, Option<i64>) =
+        indexer
+            .connection()
+            .query_row(
+                "SELECT slot, kind, option_supply, writer_supply FROM events
+                WHERE contract = ?1 ORDER BY slot DESC LIMIT 1",
+                [fixture.contract.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+    assert_eq!(
+        (slot, kind.as_str(), option_supply, writer_supply),
+        (200, "redeem", Some(9), Some(3))
+    );
+}
+
+#[test]
+fn test_ingest_is_idempotent_and_order_independent() {
+    let fixture = fixture();
+    let mut in_order = Indexer::open_in_memory(traction_client::traction::ID).unwrap();
+    ingest(&mut in_order, &fixture.transactions);
+    // ingesting the same transactions again indexes nothing
+    assert_eq!(ingest(&mut in_order, &fixture.transactions), 0);
+
+    let mut reversed = Indexer::open_in_memory(traction_client::traction::ID).unwrap();
+    let mut txs = fixture.transactions.clone();
+    txs.reverse();
+    assert_eq!(ingest(&mut reversed, &txs), 6);
+    assert_eq!(
+        in_order.open_interest(&fixture.contract).unwrap(),
+        reversed.open_interest(&fixture.contract).unwrap()
+    );
+    assert_eq!(
+        in_order.contract(&fixture.contract).unwrap(),
+        reversed.contract(&fixture.contract).unwrap()
+    );
+}
+
+#[test]
+fn test_ignores_events_of_other_programs() {
+    let fixture = fixture();
+    let mut indexer = Indexer::open_in_memory(traction_client::traction::ID).unwrap();
+    assert_eq!(ingest(&mut indexer, &[forged_transaction(&fixture)]), 0);
+    assert!(indexer.open_interest(&fixture.contract).unwrap().is_none());
+}
+
+#[test]
+fn test_ignores_other_program_ids() {
+    let fixture = fixture();
+    let mut indexer = Indexer::open_in_memory(Pubkey::new_unique()).unwrap();
+    assert_eq!(ingest(&mut indexer, &fixture.transactions), 0);
+    assert!(indexer.open_interest(&fixture.contract).unwrap().is_none());
+}
@@ -2,6 +2,41 @@
 
 use crate::*;
 
+/// Emitted on [traction::new_contract].
+#[event]
+pub struct NewContractEvent {
+    /// The [OptionsContract].
+    pub contract: Pubkey,
+    /// The [OptionsContract::creator].
+    pub creator: Pubkey,
+    /// [Mint] of the underlying asset.
+    pub underlying_mint: Pubkey,
+    /// [Mint] of the quote asset.
+    pub quote_mint: Pubkey,
+    /// The [OptionsContract::strike].
+    pub strike: u64,
+    /// The [OptionsContract::strike_units].
+    pub strike_units: u64,
+    /// The [OptionsContract::expiry_ts].
+    pub expiry_ts: i64,
+    /// If the option is a put.
+    pub is_put: bool,
+    /// The [OptionsContract::exercise_schedule].
+    pub exercise_schedule: ExerciseSchedule,
+    /// The [OptionsContract::exercise_window_secs].
+    pub exercise_window_secs: u32,
+    /// The [OptionsContract::oracle].
+    pub oracle: Pubkey,
+    /// If the option is settled in cash.
+    pub is_cash_settled: bool,
+    /// The [OptionsContract::creator_fee_kbps].
+    pub creator_fee_kbps: u64,
+    /// The [OptionsContract::creator_fee_to].
+    pub creator_fee_to: Pubkey,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
 /// Emitted on [traction::option_write].
 #[event]
 pub struct OptionWriteEvent {
@@ -11,6 +46,12 @@ pub struct OptionWriteEvent {
     pub writer: Pubkey,
     /// The amount of options written.
     pub write_amount: u64,
+    /// The amount of collateral tokens deposited into the crate.
+    pub collateral_amount: u64,
+    /// The supply of options after writing.
+    pub option_supply: u64,
+    /// The supply of writer tokens after writing.
+    pub writer_supply: u64,
     /// Timestamp of the event.
     pub timestamp: i64,
 }
@@ -24,6 +65,14 @@ pub struct OptionRedeemEvent {
     pub redeemer: Pubkey,
     /// The amount of writer tokens redeemed.
     pub writer_amount: u64,
+    /// The amount of collateral tokens withdrawn from the crate.
+    pub collateral_amount: u64,
+    /// The amount of exercise tokens withdrawn from the crate.
+    pub exercise_amount: u64,
+    /// The supply of options after redeeming.
+    pub option_supply: u64,
+    /// The supply of writer tokens after redeeming.
+    pub writer_supply: u64,
     /// Timestamp of the event.
     pub timestamp: i64,
 }
@@ -37,6 +86,149 @@ pub struct OptionExerciseEvent {
     pub exerciser: Pubkey,
     /// The amount of options exercised.
     pub option_amount: u64,
+    /// The amount of exercise tokens paid by the exerciser, including fees.
+    pub exercise_amount: u64,
+    /// The amount of exercise tokens paid to the protocol.
+    pub exercise_fee: u64,
+    /// The amount of exercise tokens paid to the [OptionsContract::creator_fee_to].
+    pub creator_fee: u64,
+    /// The amount of collateral tokens withdrawn from the crate.
+    pub collateral_amount: u64,
+    /// The supply of options after exercising.
+    pub option_supply: u64,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::option_close].
+#[event]
+pub struct OptionCloseEvent {
+    /// The [OptionsContract].
+    pub contract: Pubkey,
+    /// The account that closed the position.
+    pub closer: Pubkey,
+    /// The amount of options and writer tokens burned.
+    pub close_amount: u64,
+    /// The amount of collateral tokens withdrawn from the crate.
+    pub collateral_amount: u64,
+    /// The supply of options after closing.
+    pub option_supply: u64,
+    /// The supply of writer tokens after closing.
+    pub writer_supply: u64,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::close_contract].
+#[event]
+pub struct CloseContractEvent {
+    /// The [OptionsContract].
+    pub contract: Pubkey,
+    /// The [OptionsContract::payer] that received the rent.
+    pub payer: Pubkey,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::sweep_fees].
+#[event]
+pub struct SweepFeesEvent {
+    /// The [Mint] of the fees swept.
+    pub mint: Pubkey,
+    /// The token account the fees were sent to.
+    pub treasury_tokens: Pubkey,
+    /// The amount of fees swept.
+    pub amount: u64,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::set_protocol_params].
+#[event]
+pub struct SetProtocolParamsEvent {
+    /// The admin that set the parameters.
+    pub admin: Pubkey,
+    /// The previous exercise fee, in thousands of BPS.
+    pub prev_exercise_fee_kbps: u64,
+    /// The new exercise fee, in thousands of BPS.
+    pub exercise_fee_kbps: u64,
+    /// The previous treasury.
+    pub prev_treasury: Pubkey,
+    /// The new treasury.
+    pub treasury: Pubkey,
+    /// The previous maximum tenor, in seconds.
+    pub prev_max_tenor_secs: u64,
+    /// The new maximum tenor, in seconds.
+    pub max_tenor_secs: u64,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::new_market_params] and [traction::set_market_params].
+#[event]
+pub struct SetMarketParamsEvent {
+    /// The [MarketParams].
+    pub market_params: Pubkey,
+    /// The admin that set the parameters.
+    pub admin: Pubkey,
+    /// The new strike tick.
+    pub strike_tick: u64,
+    /// The new expiry period, in seconds.
+    pub expiry_period_secs: i64,
+    /// The new expiry offset, in seconds.
+    pub expiry_offset_secs: i64,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::transfer_admin].
+#[event]
+pub struct TransferAdminEvent {
+    /// The current admin.
+    pub admin: Pubkey,
+    /// The proposed admin.
+    pub pending_admin: Pubkey,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::accept_admin].
+#[event]
+pub struct AcceptAdminEvent {
+    /// The previous admin.
+    pub prev_admin: Pubkey,
+    /// The new admin.
+    pub admin: Pubkey,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::settle_contract].
+#[event]
+pub struct ContractSettleEvent {
+    /// The [OptionsContract].
+    pub contract: Pubkey,
+    /// The settlement price.
+    pub settlement_price: u64,
+    /// Timestamp of the event.
+    pub timestamp: i64,
+}
+
+/// Emitted on [traction::option_settle].
+#[event]
+pub struct OptionSettleEvent {
+    /// The [OptionsContract].
+    pub contract: Pubkey,
+    /// The holder of the options.
+    pub holder: Pubkey,
+    /// The account that settled the options: the holder or their delegate.
+    pub settler: Pubkey,
+    /// The amount of options settled.
+    pub option_amount: u64,
+    /// The amount of collateral paid to the holder.
+    pub settlement_amount: u64,
+    /// The supply of options after settling.
+    pub option_supply: u64,
     /// Timestamp of the event.
     pub timestamp: i64,
 }
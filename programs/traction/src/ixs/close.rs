@@ -0,0 +1,104 @@
+//! Handles [crate::traction::option_close].
+
+use crate::*;
+use anchor_spl::token;
+
+impl<'info> OptionClose<'info> {
+    /// Burns matched options and writer tokens, releasing their collateral.
+    pub fn close(&mut self, close_amount: u64) -> ProgramResult {
+        // Burn closer's option tokens
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Burn {
+                    mint: self.option_mint.to_account_info(),
+                    to: self.option_token_source.to_account_info(),
+                    authority: self.closer_authority.to_account_info(),
+                },
+            ),
+            close_amount,
+        )?;
+
+        // Burn closer's writer tokens
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Burn {
+                    mint: self.writer_mint.to_account_info(),
+                    to: self.writer_token_source.to_account_info(),
+                    authority: self.closer_authority.to_account_info(),
+                },
+            ),
+            close_amount,
+        )?;
+
+        // send the collateral backing the closed options from the crate to the closer
+        let collateral_amount = unwrap_int!(self
+            .contract
+            .calculate_collateral_amount_for_options(close_amount));
+        let seeds: &[&[&[u8]]] = gen_contract_signer_seeds!(self.contract);
+        crate_token::cpi::withdraw(
+            CpiContext::new_with_signer(
+                self.crate_token_program.to_account_info(),
+                crate_token::cpi::accounts::Withdraw {
+                    crate_token: self.writer_crate_token.to_account_info(),
+                    crate_underlying: self.crate_collateral_tokens.to_account_info(),
+                    withdraw_authority: self.contract.to_account_info(),
+                    withdraw_destination: self.collateral_token_destination.to_account_info(),
+                    // no fees here
+                    author_fee_destination: self.collateral_token_destination.to_account_info(),
+                    protocol_fee_destination: self.collateral_token_destination.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+                seeds,
+            ),
+            collateral_amount,
+        )?;
+
+        let contract = &mut self.contract;
+        contract.total_closed = unwrap_int!(contract.total_closed.checked_add(close_amount));
+
+        emit!(OptionCloseEvent {
+            contract: self.contract.key(),
+            closer: self.closer_authority.key(),
+            close_amount,
+            collateral_amount,
+            option_supply: unwrap_int!(self.option_mint.supply.checked_sub(close_amount)),
+            writer_supply: unwrap_int!(self.writer_mint.supply.checked_sub(close_amount)),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        assert_collateralized(
+            &self.contract,
+            &mut self.crate_collateral_tokens,
+            &mut self.option_mint,
+        )
+    }
+}
+
+impl<'info> Validate<'info> for OptionClose<'info> {
+    fn validate(&self) -> ProgramResult {
+        // after expiry, writers should redeem instead.
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(now < self.contract.expiry_ts, ContractExpired);
+
+        assert_keys_eq!(self.option_mint, self.contract.option_mint);
+        assert_keys_eq!(self.option_token_source.owner, self.closer_authority);
+        assert_keys_eq!(self.option_token_source.mint, self.contract.option_mint);
+        assert_keys_eq!(self.writer_mint, self.contract.writer_mint);
+        assert_keys_eq!(self.writer_token_source.owner, self.closer_authority);
+        assert_keys_eq!(self.writer_token_source.mint, self.contract.writer_mint);
+
+        assert_keys_eq!(self.writer_crate_token, self.contract.writer_crate);
+        assert_keys_eq!(
+            self.crate_collateral_tokens,
+            self.contract.crate_collateral_tokens
+        );
+        assert_keys_eq!(
+            self.collateral_token_destination.mint,
+            self.contract.collateral_mint()
+        );
+
+        Ok(())
+    }
+}
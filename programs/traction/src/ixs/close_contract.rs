@@ -0,0 +1,94 @@
+//! Handles [crate::traction::close_contract].
+
+use crate::*;
+
+impl<'info> CloseContract<'info> {
+    /// Closes the [OptionsContract] and its [OptionSeries].
+    ///
+    /// The accounts themselves are closed by the `close` constraints.
+    pub fn close_contract(&self) -> ProgramResult {
+        // nothing in the crate is owed to writers or holders anymore
+        let seeds: &[&[&[u8]]] = gen_contract_signer_seeds!(self.contract);
+        for (crate_underlying, destination) in [
+            (&self.crate_collateral_tokens, &self.collateral_destination),
+            (&self.crate_exercise_tokens, &self.exercise_destination),
+        ] {
+            if crate_underlying.amount == 0 {
+                continue;
+            }
+            crate_token::cpi::withdraw(
+                CpiContext::new_with_signer(
+                    self.crate_token_program.to_account_info(),
+                    crate_token::cpi::accounts::Withdraw {
+                        crate_token: self.writer_crate_token.to_account_info(),
+                        crate_underlying: crate_underlying.to_account_info(),
+                        withdraw_authority: self.contract.to_account_info(),
+                        withdraw_destination: destination.to_account_info(),
+                        // no fees here
+                        author_fee_destination: destination.to_account_info(),
+                        protocol_fee_destination: destination.to_account_info(),
+                        token_program: self.token_program.to_account_info(),
+                    },
+                    seeds,
+                ),
+                crate_underlying.amount,
+            )?;
+        }
+
+        emit!(CloseContractEvent {
+            contract: self.contract.key(),
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CloseContract<'info> {
+    fn validate(&self) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let exercise_deadline_ts = unwrap_int!(self.contract.exercise_deadline_ts());
+        invariant!(now >= exercise_deadline_ts, ContractNotYetExpired);
+
+        assert_keys_eq!(self.series, self.contract.series);
+        assert_keys_eq!(self.series.contract, self.contract);
+        assert_keys_eq!(self.writer_mint, self.contract.writer_mint);
+        assert_keys_eq!(self.option_mint, self.contract.option_mint);
+        assert_keys_eq!(self.payer, self.contract.payer);
+
+        assert_keys_eq!(self.writer_crate_token, self.contract.writer_crate);
+        assert_keys_eq!(
+            self.crate_collateral_tokens,
+            self.contract.crate_collateral_tokens
+        );
+        assert_keys_eq!(
+            self.crate_exercise_tokens,
+            self.contract.crate_exercise_tokens
+        );
+        assert_keys_eq!(self.collateral_destination.owner, self.payer);
+        assert_keys_eq!(
+            self.collateral_destination.mint,
+            self.contract.collateral_mint()
+        );
+        assert_keys_eq!(self.exercise_destination.owner, self.payer);
+        assert_keys_eq!(
+            self.exercise_destination.mint,
+            self.contract.exercise_mint()
+        );
+
+        // all collateral and exercise proceeds have been redeemed
+        invariant!(self.writer_mint.supply == 0, WriterTokensOutstanding);
+        // holders of settled options can still claim collateral
+        if self.contract.has_oracle() {
+            invariant!(self.contract.is_settled, ContractNotSettled);
+            let reserved_collateral =
+                unwrap_int!(self.contract.calculate_settlement_amount_for_options(
+                    self.option_mint.supply,
+                    self.contract.settlement_price
+                ));
+            invariant!(reserved_collateral == 0, SettlementOutstanding);
+        }
+
+        Ok(())
+    }
+}
@@ -5,16 +5,31 @@ use anchor_spl::token;
 
 impl<'info> OptionExercise<'info> {
     /// Exercise the option
-    pub fn exercise(&self, option_amount: u64) -> ProgramResult {
+    pub fn exercise(&mut self, option_amount: u64) -> ProgramResult {
         let contract = &self.contract;
+        // amounts paid into the crate round up, so this is only zero when nothing is exercised
         let exercise_amount: u64 =
             unwrap_int!(contract.calculate_exercise_amount_for_options(option_amount));
+        invariant!(exercise_amount > 0, ZeroExercisePayment);
+        invariant!(
+            option_amount >= unwrap_int!(contract.min_exercise_amount()),
+            ExerciseAmountTooSmall
+        );
+        let collateral_amount: u64 =
+            unwrap_int!(contract.calculate_collateral_amount_for_options(option_amount));
 
         // Send exercise tokens from exerciser to the writer crate
-        let exercise_fee = unwrap_int!(exercise_amount
-            .checked_mul(EXERCISE_FEE_KBPS)
-            .and_then(|f| f.checked_div(10_000 * 1_000)));
-        let exercise_received = unwrap_int!(exercise_amount.checked_sub(exercise_fee));
+        let exercise_fee = unwrap_int!(calculate_fee_kbps(
+            exercise_amount,
+            self.protocol_config.exercise_fee_kbps
+        ));
+        let creator_fee = unwrap_int!(calculate_fee_kbps(
+            exercise_amount,
+            contract.creator_fee_kbps
+        ));
+        let exercise_received = unwrap_int!(exercise_amount
+            .checked_sub(exercise_fee)
+            .and_then(|v| v.checked_sub(creator_fee)));
 
         // exercise quote
         token::transfer(
@@ -40,6 +55,20 @@ impl<'info> OptionExercise<'info> {
             ),
             exercise_fee,
         )?;
+        // creator fee
+        if creator_fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    token::Transfer {
+                        from: self.exercise_token_source.to_account_info(),
+                        to: self.creator_fee_destination.to_account_info(),
+                        authority: self.exerciser_authority.to_account_info(),
+                    },
+                ),
+                creator_fee,
+            )?;
+        }
 
         // Burn exerciser's option tokens
         token::burn(
@@ -71,24 +100,45 @@ impl<'info> OptionExercise<'info> {
                 },
                 seeds,
             ),
-            option_amount,
+            collateral_amount,
         )?;
 
+        let contract = &mut self.contract;
+        contract.total_exercised = unwrap_int!(contract.total_exercised.checked_add(option_amount));
+
         emit!(OptionExerciseEvent {
             contract: self.contract.key(),
             exerciser: self.exerciser_authority.key(),
             option_amount,
+            exercise_amount,
+            exercise_fee,
+            creator_fee,
+            collateral_amount,
+            option_supply: unwrap_int!(self.option_mint.supply.checked_sub(option_amount)),
             timestamp: Clock::get()?.unix_timestamp,
         });
 
-        Ok(())
+        assert_collateralized(
+            &self.contract,
+            &mut self.crate_collateral_tokens,
+            &mut self.option_mint,
+        )
     }
 }
 
 impl<'info> Validate<'info> for OptionExercise<'info> {
     fn validate(&self) -> ProgramResult {
+        invariant!(!self.contract.is_cash_settled, CashSettledContract);
+
         let now = Clock::get()?.unix_timestamp;
-        invariant!(now < self.contract.expiry_ts, ContractExpired);
+        let exercise_deadline_ts = unwrap_int!(self.contract.exercise_deadline_ts());
+        invariant!(now < exercise_deadline_ts, ContractExpired);
+        invariant!(
+            self.contract
+                .exercise_schedule
+                .is_exercisable_at(now, self.contract.expiry_ts),
+            OutsideExerciseWindow
+        );
 
         assert_keys_eq!(self.exercise_token_source.owner, self.exerciser_authority);
         assert_keys_eq!(
@@ -109,10 +159,20 @@ impl<'info> Validate<'info> for OptionExercise<'info> {
         );
         assert_keys_eq!(
             self.collateral_token_destination.mint,
-            self.contract.underlying_mint
+            self.contract.collateral_mint()
         );
 
         assert_keys_eq!(self.exercise_fee_destination.owner, FEE_OWNER);
+        if self.contract.creator_fee_kbps > 0 {
+            assert_keys_eq!(
+                self.creator_fee_destination.owner,
+                self.contract.creator_fee_to
+            );
+            assert_keys_eq!(
+                self.creator_fee_destination.mint,
+                self.contract.exercise_mint()
+            );
+        }
 
         Ok(())
     }
@@ -0,0 +1,104 @@
+//! Handles [crate::traction::new_option_market], [crate::traction::new_market_params],
+//! and [crate::traction::set_market_params].
+
+use crate::*;
+
+impl<'info> NewOptionMarket<'info> {
+    /// Creates a new [OptionMarket].
+    pub fn new_option_market(&mut self, bump: u8) -> ProgramResult {
+        let market = &mut self.market;
+        market.bump = bump;
+        market.underlying_mint = self.underlying_mint.key();
+        market.quote_mint = self.quote_mint.key();
+        market.num_series = 0;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewOptionMarket<'info> {
+    fn validate(&self) -> ProgramResult {
+        assert_keys_neq!(self.underlying_mint, self.quote_mint, UselessMints);
+        Ok(())
+    }
+}
+
+impl<'info> NewMarketParams<'info> {
+    /// Creates new [MarketParams].
+    pub fn new_market_params(
+        &mut self,
+        bump: u8,
+        strike_tick: u64,
+        expiry_period_secs: i64,
+        expiry_offset_secs: i64,
+    ) -> ProgramResult {
+        invariant!(
+            expiry_period_secs >= 0 && expiry_offset_secs >= 0,
+            InvalidExpiryGrid
+        );
+
+        let market_params = &mut self.market_params;
+        market_params.bump = bump;
+        market_params.underlying_mint = self.underlying_mint.key();
+        market_params.quote_mint = self.quote_mint.key();
+        market_params.strike_tick = strike_tick;
+        market_params.expiry_period_secs = expiry_period_secs;
+        market_params.expiry_offset_secs = expiry_offset_secs;
+
+        emit!(SetMarketParamsEvent {
+            market_params: market_params.key(),
+            admin: self.admin.key(),
+            strike_tick,
+            expiry_period_secs,
+            expiry_offset_secs,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewMarketParams<'info> {
+    fn validate(&self) -> ProgramResult {
+        assert_keys_eq!(self.admin, self.protocol_config.admin, Unauthorized);
+        assert_keys_neq!(self.underlying_mint, self.quote_mint, UselessMints);
+        Ok(())
+    }
+}
+
+impl<'info> SetMarketParams<'info> {
+    /// Updates the [MarketParams].
+    pub fn set_market_params(
+        &mut self,
+        strike_tick: u64,
+        expiry_period_secs: i64,
+        expiry_offset_secs: i64,
+    ) -> ProgramResult {
+        invariant!(
+            expiry_period_secs >= 0 && expiry_offset_secs >= 0,
+            InvalidExpiryGrid
+        );
+
+        let market_params = &mut self.market_params;
+        market_params.strike_tick = strike_tick;
+        market_params.expiry_period_secs = expiry_period_secs;
+        market_params.expiry_offset_secs = expiry_offset_secs;
+
+        emit!(SetMarketParamsEvent {
+            market_params: market_params.key(),
+            admin: self.admin.key(),
+            strike_tick,
+            expiry_period_secs,
+            expiry_offset_secs,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetMarketParams<'info> {
+    fn validate(&self) -> ProgramResult {
+        assert_keys_eq!(self.admin, self.protocol_config.admin, Unauthorized);
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
 //! Instruction handlers.
 
+mod close;
+mod close_contract;
 mod exercise;
+mod market;
 mod new;
+mod price_oracle;
+mod protocol_config;
 mod redeem;
+mod settle;
+mod settle_contract;
+mod sweep_fees;
 mod write;
@@ -6,14 +6,62 @@ use crate::*;
 
 impl<'info> NewContract<'info> {
     /// Creates a new [OptionsContract].
+    #[allow(clippy::too_many_arguments)]
     pub fn new_contract(
         &mut self,
         strike: u64,
         expiry_ts: i64,
         is_put: bool,
+        exercise_schedule: ExerciseSchedule,
+        is_cash_settled: bool,
         contract_bump: u8,
         crate_bump: u8,
+        exercise_window_secs: u32,
+        creator_fee_kbps: u64,
+        creator_fee_to: Pubkey,
+        series_bump: u8,
     ) -> ProgramResult {
+        invariant!(strike > 0, ZeroStrike);
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(expiry_ts > now, ExpiryInPast);
+        let max_tenor_secs = self.protocol_config.max_tenor_secs;
+        if max_tenor_secs != 0 {
+            let tenor_secs = unwrap_int!(expiry_ts.checked_sub(now));
+            invariant!((tenor_secs as u64) <= max_tenor_secs, TenorTooLong);
+        }
+        if !self.market_params.data_is_empty() {
+            let market_params: Account<MarketParams> =
+                Account::try_from(&self.market_params.to_account_info())?;
+            invariant!(market_params.is_valid_strike(strike), StrikeNotOnTick);
+            invariant!(market_params.is_valid_expiry(expiry_ts), ExpiryNotOnGrid);
+        }
+        invariant!(creator_fee_kbps <= MAX_CREATOR_FEE_KBPS, CreatorFeeTooHigh);
+        if creator_fee_kbps == 0 {
+            // contracts without a fee share a single address per series
+            assert_keys_eq!(
+                creator_fee_to,
+                Pubkey::default(),
+                CreatorFeeRecipientWithoutFee
+            );
+        }
+        invariant!(exercise_schedule.is_valid(), InvalidExerciseSchedule);
+        if is_cash_settled {
+            // cash-settled options are only settled at expiry
+            invariant!(
+                exercise_schedule == ExerciseSchedule::default(),
+                InvalidExerciseSchedule
+            );
+            assert_keys_neq!(self.oracle, Pubkey::default(), NoOracle);
+        }
+        if self.oracle.key() != Pubkey::default() {
+            // ensure the oracle can price the contract
+            load_oracle_price(
+                &self.oracle.to_account_info(),
+                self.underlying_mint.key(),
+                self.quote_mint.key(),
+            )?;
+        }
+
         // initialize the writer crate
         // The writer crate holds all options.
         crate_token::cpi::new_crate(
@@ -42,9 +90,16 @@ impl<'info> NewContract<'info> {
         contract.underlying_mint = self.underlying_mint.key();
         contract.quote_mint = self.quote_mint.key();
         contract.strike = strike;
+        contract.strike_units =
+            unwrap_int!(10_u64.checked_pow(self.underlying_mint.decimals.into()));
         contract.expiry_ts = expiry_ts;
+        contract.exercise_window_secs = exercise_window_secs;
         contract.is_put = is_put;
+        contract.exercise_schedule = exercise_schedule;
         contract.bump = contract_bump;
+        contract.payer = self.payer.key();
+        contract.creator = self.creator.key();
+        contract.created_at = now;
 
         contract.writer_mint = self.writer_crate.crate_mint.key();
         contract.writer_crate = self.writer_crate.crate_token.key();
@@ -58,6 +113,42 @@ impl<'info> NewContract<'info> {
             &contract.exercise_mint(),
         );
         contract.option_mint = self.option_mint.key();
+        contract.series = self.series.key();
+
+        contract.creator_fee_kbps = creator_fee_kbps;
+        contract.creator_fee_to = creator_fee_to;
+
+        contract.is_cash_settled = is_cash_settled;
+        contract.oracle = self.oracle.key();
+
+        let market = &mut self.market;
+        let series = &mut self.series;
+        series.market = market.key();
+        series.index = market.num_series;
+        series.bump = series_bump;
+        series.contract = contract.key();
+        series.strike = strike;
+        series.expiry_ts = expiry_ts;
+        series.is_put = is_put;
+        market.num_series = unwrap_int!(market.num_series.checked_add(1));
+
+        emit!(NewContractEvent {
+            contract: self.contract.key(),
+            creator: self.creator.key(),
+            underlying_mint: self.underlying_mint.key(),
+            quote_mint: self.quote_mint.key(),
+            strike,
+            strike_units: self.contract.strike_units,
+            expiry_ts,
+            is_put,
+            exercise_schedule,
+            exercise_window_secs,
+            oracle: self.oracle.key(),
+            is_cash_settled,
+            creator_fee_kbps,
+            creator_fee_to,
+            timestamp: now,
+        });
 
         Ok(())
     }
@@ -73,6 +164,12 @@ impl<'info> Validate<'info> for NewContract<'info> {
         invariant!(self.option_mint.supply == 0, OptionMintMustHaveZeroSupply);
 
         assert_keys_neq!(self.underlying_mint, self.quote_mint, UselessMints);
+        assert_keys_eq!(
+            self.market.underlying_mint,
+            self.underlying_mint,
+            MarketMismatch
+        );
+        assert_keys_eq!(self.market.quote_mint, self.quote_mint, MarketMismatch);
 
         invariant!(
             self.underlying_mint.decimals == self.option_mint.decimals,
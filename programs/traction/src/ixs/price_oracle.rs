@@ -0,0 +1,39 @@
+//! Handles [crate::traction::new_price_oracle] and [crate::traction::set_oracle_price].
+
+use crate::*;
+
+impl<'info> NewPriceOracle<'info> {
+    /// Creates a new [PriceOracle].
+    pub fn new_price_oracle(&mut self) -> ProgramResult {
+        let oracle = &mut self.oracle;
+        oracle.authority = self.authority.key();
+        oracle.underlying_mint = self.underlying_mint.key();
+        oracle.quote_mint = self.quote_mint.key();
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewPriceOracle<'info> {
+    fn validate(&self) -> ProgramResult {
+        invariant!(cfg!(feature = "test-oracle"), TestOracleDisabled);
+        Ok(())
+    }
+}
+
+impl<'info> SetOraclePrice<'info> {
+    /// Publishes a new price.
+    pub fn set_oracle_price(&mut self, price: u64) -> ProgramResult {
+        let oracle = &mut self.oracle;
+        oracle.price = price;
+        oracle.publish_ts = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SetOraclePrice<'info> {
+    fn validate(&self) -> ProgramResult {
+        invariant!(cfg!(feature = "test-oracle"), TestOracleDisabled);
+        assert_keys_eq!(self.authority, self.oracle.authority, Unauthorized);
+        Ok(())
+    }
+}
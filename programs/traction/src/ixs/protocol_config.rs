@@ -0,0 +1,139 @@
+//! Handles [crate::traction::new_protocol_config], [crate::traction::set_protocol_params],
+//! [crate::traction::transfer_admin], and [crate::traction::accept_admin].
+
+use crate::*;
+use anchor_lang::solana_program::{
+    bpf_loader_upgradeable::UpgradeableLoaderState, program_utils::limited_deserialize,
+};
+
+impl<'info> NewProtocolConfig<'info> {
+    /// Creates the [ProtocolConfig].
+    pub fn new_protocol_config(&mut self, bump: u8, treasury: Pubkey) -> ProgramResult {
+        let protocol_config = &mut self.protocol_config;
+        protocol_config.bump = bump;
+        protocol_config.admin = self.admin.key();
+        protocol_config.pending_admin = Pubkey::default();
+        protocol_config.treasury = treasury;
+        protocol_config.exercise_fee_kbps = DEFAULT_EXERCISE_FEE_KBPS;
+        protocol_config.max_tenor_secs = 0;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for NewProtocolConfig<'info> {
+    fn validate(&self) -> ProgramResult {
+        // the config controls every fee, so it may not be created by
+        // whoever happens to call this first after the program is deployed.
+        let (program_data, _) =
+            Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::ID);
+        assert_keys_eq!(self.program_data, program_data);
+        assert_keys_eq!(*self.program_data.owner, bpf_loader_upgradeable::ID);
+
+        let data = self.program_data.try_borrow_data()?;
+        let offset = UpgradeableLoaderState::programdata_data_offset()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let state: UpgradeableLoaderState = limited_deserialize(&data, offset as u64)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        // programs without an upgrade authority cannot create the config
+        let upgrade_authority = match state {
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address: Some(upgrade_authority),
+                ..
+            } => upgrade_authority,
+            _ => return program_err!(Unauthorized),
+        };
+        assert_keys_eq!(self.admin, upgrade_authority, Unauthorized);
+
+        Ok(())
+    }
+}
+
+impl<'info> ProtocolConfigAdmin<'info> {
+    /// Sets the parameters of the [ProtocolConfig].
+    pub fn set_protocol_params(
+        &mut self,
+        exercise_fee_kbps: u64,
+        treasury: Pubkey,
+        max_tenor_secs: u64,
+    ) -> ProgramResult {
+        invariant!(
+            exercise_fee_kbps <= MAX_EXERCISE_FEE_KBPS,
+            ExerciseFeeTooHigh
+        );
+
+        let protocol_config = &mut self.protocol_config;
+        let prev_exercise_fee_kbps = protocol_config.exercise_fee_kbps;
+        let prev_treasury = protocol_config.treasury;
+        let prev_max_tenor_secs = protocol_config.max_tenor_secs;
+        protocol_config.exercise_fee_kbps = exercise_fee_kbps;
+        protocol_config.treasury = treasury;
+        protocol_config.max_tenor_secs = max_tenor_secs;
+
+        emit!(SetProtocolParamsEvent {
+            admin: self.admin.key(),
+            prev_exercise_fee_kbps,
+            exercise_fee_kbps,
+            prev_treasury,
+            treasury,
+            prev_max_tenor_secs,
+            max_tenor_secs,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Proposes a new admin.
+    pub fn transfer_admin(&mut self, new_admin: Pubkey) -> ProgramResult {
+        self.protocol_config.pending_admin = new_admin;
+
+        emit!(TransferAdminEvent {
+            admin: self.admin.key(),
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for ProtocolConfigAdmin<'info> {
+    fn validate(&self) -> ProgramResult {
+        assert_keys_eq!(self.admin, self.protocol_config.admin, Unauthorized);
+        Ok(())
+    }
+}
+
+impl<'info> AcceptAdmin<'info> {
+    /// Makes the pending admin the admin.
+    pub fn accept_admin(&mut self) -> ProgramResult {
+        let protocol_config = &mut self.protocol_config;
+        let prev_admin = protocol_config.admin;
+        protocol_config.admin = protocol_config.pending_admin;
+        protocol_config.pending_admin = Pubkey::default();
+
+        emit!(AcceptAdminEvent {
+            prev_admin,
+            admin: self.pending_admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for AcceptAdmin<'info> {
+    fn validate(&self) -> ProgramResult {
+        assert_keys_neq!(
+            self.protocol_config.pending_admin,
+            Pubkey::default(),
+            NoPendingAdmin
+        );
+        assert_keys_eq!(
+            self.pending_admin,
+            self.protocol_config.pending_admin,
+            Unauthorized
+        );
+        Ok(())
+    }
+}
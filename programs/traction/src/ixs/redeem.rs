@@ -7,10 +7,30 @@ use num_traits::ToPrimitive;
 impl<'info> OptionRedeem<'info> {
     /// Helper to redeem the writer crate.
     /// This is not necessary.
-    pub fn redeem(&self, writer_amount: u64) -> ProgramResult {
-        // calculate share of the tokens
-        let collateral_amount = unwrap_int!((self.crate_collateral_tokens.amount as u128)
-            .checked_mul(self.writer_token_source.amount.into())
+    pub fn redeem(&mut self, writer_amount: u64) -> ProgramResult {
+        // collateral owed to holders of settled options is not available to writers
+        let reserved_collateral = if self.contract.has_oracle() {
+            unwrap_int!(self.contract.calculate_settlement_amount_for_options(
+                self.option_mint.supply,
+                self.contract.settlement_price
+            ))
+        } else {
+            0
+        };
+        let available_collateral = unwrap_int!(self
+            .crate_collateral_tokens
+            .amount
+            .checked_sub(reserved_collateral));
+
+        // calculate the redeemed share of the tokens.
+        // shares are taken against the writer supply before burning, so partial
+        // redemptions leave the remaining writer tokens with the same per-token claim.
+        let collateral_amount = unwrap_int!((available_collateral as u128)
+            .checked_mul(writer_amount.into())
+            .and_then(|v| v.checked_div(self.writer_mint.supply.into()))
+            .and_then(|v| v.to_u64()));
+        let exercise_amount = unwrap_int!((self.crate_exercise_tokens.amount as u128)
+            .checked_mul(writer_amount.into())
             .and_then(|v| v.checked_div(self.writer_mint.supply.into()))
             .and_then(|v| v.to_u64()));
 
@@ -49,10 +69,6 @@ impl<'info> OptionRedeem<'info> {
 
         // redeem exercise tokens if they are different from the collateral tokens
         if self.crate_collateral_tokens.mint != self.crate_exercise_tokens.mint {
-            let exercise_amount = unwrap_int!((self.crate_exercise_tokens.amount as u128)
-                .checked_mul(self.writer_token_source.amount.into())
-                .and_then(|v| v.checked_div(self.writer_mint.supply.into()))
-                .and_then(|v| v.to_u64()));
             crate_token::cpi::withdraw(
                 CpiContext::new_with_signer(
                     self.crate_token_program.to_account_info(),
@@ -72,25 +88,42 @@ impl<'info> OptionRedeem<'info> {
             )?;
         }
 
+        let contract = &mut self.contract;
+        contract.total_redeemed = unwrap_int!(contract.total_redeemed.checked_add(writer_amount));
+
         emit!(OptionRedeemEvent {
             contract: self.contract.key(),
             redeemer: self.writer_authority.key(),
             writer_amount,
+            collateral_amount,
+            exercise_amount,
+            option_supply: self.option_mint.supply,
+            writer_supply: unwrap_int!(self.writer_mint.supply.checked_sub(writer_amount)),
             timestamp: Clock::get()?.unix_timestamp,
         });
 
-        Ok(())
+        assert_collateralized(
+            &self.contract,
+            &mut self.crate_collateral_tokens,
+            &mut self.option_mint,
+        )
     }
 }
 
 impl<'info> Validate<'info> for OptionRedeem<'info> {
     fn validate(&self) -> ProgramResult {
-        // can only redeem when the contract has expired.
+        // can only redeem when the contract has expired and options can no longer be exercised.
         let now = Clock::get()?.unix_timestamp;
-        invariant!(now >= self.contract.expiry_ts, ContractNotYetExpired);
+        let exercise_deadline_ts = unwrap_int!(self.contract.exercise_deadline_ts());
+        invariant!(now >= exercise_deadline_ts, ContractNotYetExpired);
+        if self.contract.has_oracle() {
+            invariant!(self.contract.is_settled, ContractNotSettled);
+        }
 
         assert_keys_eq!(self.writer_authority, self.writer_token_source.owner);
         assert_keys_eq!(self.writer_token_source.mint, self.contract.writer_mint);
+        assert_keys_eq!(self.writer_mint, self.contract.writer_mint);
+        assert_keys_eq!(self.option_mint, self.contract.option_mint);
         // underlying_token_destination and quote_token_destination don't really matter to validate
 
         assert_keys_eq!(self.writer_crate_token, self.contract.writer_crate);
@@ -0,0 +1,103 @@
+//! Handles [crate::traction::option_settle].
+
+use crate::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token;
+
+impl<'info> OptionSettle<'info> {
+    /// Burns options in exchange for their intrinsic value at the settlement price.
+    pub fn settle(&mut self, option_amount: u64) -> ProgramResult {
+        let contract = &self.contract;
+        let settlement_amount = unwrap_int!(contract
+            .calculate_settlement_amount_for_options(option_amount, contract.settlement_price));
+
+        // Burn holder's option tokens
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Burn {
+                    mint: self.option_mint.to_account_info(),
+                    to: self.option_token_source.to_account_info(),
+                    authority: self.settler_authority.to_account_info(),
+                },
+            ),
+            option_amount,
+        )?;
+
+        // Send the intrinsic value from the crate to the holder.
+        // Out of the money options are simply burned.
+        if settlement_amount > 0 {
+            let seeds: &[&[&[u8]]] = gen_contract_signer_seeds!(self.contract);
+            crate_token::cpi::withdraw(
+                CpiContext::new_with_signer(
+                    self.crate_token_program.to_account_info(),
+                    crate_token::cpi::accounts::Withdraw {
+                        crate_token: self.writer_crate_token.to_account_info(),
+                        crate_underlying: self.crate_collateral_tokens.to_account_info(),
+                        withdraw_authority: self.contract.to_account_info(),
+                        withdraw_destination: self.collateral_token_destination.to_account_info(),
+                        // no fees here
+                        author_fee_destination: self.collateral_token_destination.to_account_info(),
+                        protocol_fee_destination: self
+                            .collateral_token_destination
+                            .to_account_info(),
+                        token_program: self.token_program.to_account_info(),
+                    },
+                    seeds,
+                ),
+                settlement_amount,
+            )?;
+        }
+
+        let contract = &mut self.contract;
+        contract.total_settled = unwrap_int!(contract.total_settled.checked_add(option_amount));
+
+        emit!(OptionSettleEvent {
+            contract: self.contract.key(),
+            holder: self.option_token_source.owner,
+            settler: self.settler_authority.key(),
+            option_amount,
+            settlement_amount,
+            option_supply: unwrap_int!(self.option_mint.supply.checked_sub(option_amount)),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        assert_collateralized(
+            &self.contract,
+            &mut self.crate_collateral_tokens,
+            &mut self.option_mint,
+        )
+    }
+}
+
+impl<'info> Validate<'info> for OptionSettle<'info> {
+    fn validate(&self) -> ProgramResult {
+        invariant!(self.contract.is_settled, ContractNotSettled);
+
+        assert_keys_eq!(self.option_mint, self.contract.option_mint);
+        // keepers may settle on behalf of holders who have delegated their options to them
+        let settler = self.settler_authority.key();
+        invariant!(
+            self.option_token_source.owner == settler
+                || self.option_token_source.delegate == COption::Some(settler),
+            Unauthorized
+        );
+        assert_keys_eq!(self.option_token_source.mint, self.contract.option_mint);
+
+        assert_keys_eq!(self.writer_crate_token, self.contract.writer_crate);
+        assert_keys_eq!(
+            self.crate_collateral_tokens,
+            self.contract.crate_collateral_tokens
+        );
+        assert_keys_eq!(
+            self.collateral_token_destination.owner,
+            self.option_token_source.owner
+        );
+        assert_keys_eq!(
+            self.collateral_token_destination.mint,
+            self.contract.collateral_mint()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! Handles [crate::traction::settle_contract].
+
+use crate::*;
+
+impl<'info> SettleContract<'info> {
+    /// Records the settlement price.
+    pub fn settle_contract(&mut self) -> ProgramResult {
+        let contract = &mut self.contract;
+        let oracle_price = load_oracle_price(
+            &self.oracle.to_account_info(),
+            contract.underlying_mint,
+            contract.quote_mint,
+        )?;
+        // the price must reflect the market after settlement. It is recorded by the first
+        // call after it is published, however late, so holders never forfeit their value.
+        let settlement_ts = unwrap_int!(contract.settlement_ts());
+        invariant!(
+            oracle_price.publish_ts >= settlement_ts,
+            StaleSettlementPrice
+        );
+        let settlement_price = oracle_price.price;
+
+        contract.is_settled = true;
+        contract.settlement_price = settlement_price;
+
+        emit!(ContractSettleEvent {
+            contract: contract.key(),
+            settlement_price,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SettleContract<'info> {
+    fn validate(&self) -> ProgramResult {
+        invariant!(self.contract.has_oracle(), NoOracle);
+
+        let now = Clock::get()?.unix_timestamp;
+        let settlement_ts = unwrap_int!(self.contract.settlement_ts());
+        invariant!(now >= settlement_ts, ContractNotYetExpired);
+
+        invariant!(!self.contract.is_settled, ContractAlreadySettled);
+        assert_keys_eq!(self.oracle, self.contract.oracle);
+
+        Ok(())
+    }
+}
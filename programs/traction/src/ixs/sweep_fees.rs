@@ -0,0 +1,46 @@
+//! Handles [crate::traction::sweep_fees].
+
+use crate::*;
+use anchor_spl::token;
+
+impl<'info> SweepFees<'info> {
+    /// Sends all fees in the fee account to the treasury.
+    pub fn sweep_fees(&self) -> ProgramResult {
+        let amount = self.fee_tokens.amount;
+
+        let seeds: &[&[&[u8]]] = gen_fee_owner_signer_seeds!();
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.fee_tokens.to_account_info(),
+                    to: self.treasury_tokens.to_account_info(),
+                    authority: self.fee_owner.to_account_info(),
+                },
+                seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(SweepFeesEvent {
+            mint: self.fee_tokens.mint,
+            treasury_tokens: self.treasury_tokens.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for SweepFees<'info> {
+    fn validate(&self) -> ProgramResult {
+        assert_keys_eq!(self.fee_owner, FEE_OWNER);
+        assert_keys_eq!(self.fee_tokens.owner, FEE_OWNER);
+
+        assert_keys_eq!(self.treasury_tokens.owner, self.protocol_config.treasury);
+        assert_keys_eq!(self.treasury_tokens.mint, self.fee_tokens.mint);
+
+        Ok(())
+    }
+}
@@ -4,29 +4,42 @@ use crate::*;
 use anchor_spl::token;
 
 impl<'info> OptionWrite<'info> {
-    pub fn write(&self, write_amount: u64) -> ProgramResult {
+    pub fn write(&mut self, write_amount: u64) -> ProgramResult {
+        let collateral_amount = unwrap_int!(self
+            .contract
+            .calculate_write_collateral_amount_for_options(write_amount));
         let user_collateral_funding_tokens = &self.user_collateral_funding_tokens;
         invariant!(
-            user_collateral_funding_tokens.amount >= write_amount,
+            user_collateral_funding_tokens.amount >= collateral_amount,
             InsufficientCollateral
         );
 
-        self.pull_payment(write_amount)?;
+        self.pull_payment(collateral_amount)?;
         self.issue_writer_tokens(write_amount)?;
         self.mint_options(write_amount)?;
 
+        let contract = &mut self.contract;
+        contract.total_written = unwrap_int!(contract.total_written.checked_add(write_amount));
+
         emit!(OptionWriteEvent {
             contract: self.contract.key(),
             writer: self.writer_authority.key(),
             write_amount,
+            collateral_amount,
+            option_supply: unwrap_int!(self.option_mint.supply.checked_add(write_amount)),
+            writer_supply: unwrap_int!(self.writer_mint.supply.checked_add(write_amount)),
             timestamp: Clock::get()?.unix_timestamp,
         });
 
-        Ok(())
+        assert_collateralized(
+            &self.contract,
+            &mut self.crate_collateral_tokens,
+            &mut self.option_mint,
+        )
     }
 
-    /// transfer writer's tokens to the crate
-    fn pull_payment(&self, write_amount: u64) -> ProgramResult {
+    /// transfer writer's collateral to the crate
+    fn pull_payment(&self, collateral_amount: u64) -> ProgramResult {
         token::transfer(
             CpiContext::new(
                 self.token_program.to_account_info(),
@@ -36,7 +49,7 @@ impl<'info> OptionWrite<'info> {
                     authority: self.writer_authority.to_account_info(),
                 },
             ),
-            write_amount,
+            collateral_amount,
         )
     }
 
@@ -1,7 +1,8 @@
-//! Program for issuing American options.
+//! Program for issuing American, European, and Bermudan options.
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate_token::CrateToken;
 use vipers::*;
@@ -9,9 +10,13 @@ use vipers::*;
 mod events;
 mod ixs;
 mod macros;
+mod oracle;
+mod solvency;
 mod state;
 
 pub use events::*;
+pub use oracle::*;
+pub use solvency::*;
 pub use state::*;
 
 /// Owner of all accounts that receives fees earned by the protocol.
@@ -22,8 +27,14 @@ pub static FEE_OWNER: Pubkey =
 /// Bump seed.
 pub const FEE_OWNER_BUMP: u8 = 255;
 
-/// Thousands of BPS of the exercise fee.
-pub const EXERCISE_FEE_KBPS: u64 = 1_000;
+/// Thousands of BPS of the exercise fee that a new [ProtocolConfig] starts with.
+pub const DEFAULT_EXERCISE_FEE_KBPS: u64 = 1_000;
+
+/// Maximum exercise fee that the [ProtocolConfig::admin] may set, in thousands of BPS. (1%)
+pub const MAX_EXERCISE_FEE_KBPS: u64 = 100_000;
+
+/// Maximum creator fee of an [OptionsContract], in thousands of BPS. (1%)
+pub const MAX_CREATOR_FEE_KBPS: u64 = 100_000;
 
 declare_id!("TRXf3r361YRfV6Zktov3nvdEqJwAuCowkjh4PUUBYEc");
 
@@ -34,28 +45,109 @@ pub mod traction {
 
     /// Defines a new [OptionsContract].
     ///
-    /// An [OptionsContract] is uniquely defined by four parameters:
+    /// An [OptionsContract] is uniquely defined by eight parameters:
     /// - `underlying_mint`, the mint of the underlying token
     /// - `quote_mint`, the mint of the quote token
     /// - `strike`, the strike price to purchase 10**underlying_decimals of the underlying
     /// - `expiry_ts`, when the option expires.
     /// - `is_put`, which defines whether the option is a put or a call.
+    /// - `exercise_schedule`, which defines when the option may be exercised.
+    /// - `is_cash_settled`, which defines whether the option is settled in cash rather than
+    ///   by delivering the exercise tokens.
+    /// - `oracle`, the oracle which provides the settlement price. This is required for
+    ///   cash-settled options. Physically settled options may use the system program
+    ///   to opt out of automatic exercise.
+    ///
+    /// Cash-settled options cannot be exercised. Instead, after expiry, anyone may record
+    /// the settlement price via [traction::settle_contract], and holders then receive
+    /// the intrinsic value of their options from the collateral via [traction::option_settle].
+    ///
+    /// Physically settled options with an oracle are automatically exercised in the same way
+    /// once the exercise window after expiry closes, whatever their [ExerciseSchedule]:
+    /// in-the-money options that were not exercised may be settled for their intrinsic value,
+    /// net of the strike, at an oracle price published after the window closes, however late
+    /// it is recorded. Options without an oracle are never exercised automatically, so
+    /// holders must exercise them before the exercise window closes.
+    ///
+    /// Options may still be exercised for `exercise_window_secs` after expiry,
+    /// after which writers may redeem. The window is part of the contract's address.
     ///
-    /// Anyone can create the [OptionsContract].
+    /// Anyone can create the [OptionsContract]. The creator may charge a fee of up to
+    /// [MAX_CREATOR_FEE_KBPS] on every exercise, paid to `creator_fee_to`.
+    /// The fee and its recipient are part of the contract's address, so a contract
+    /// with a fee never occupies the address of the same series without one.
+    /// Contracts without a fee must use the default [Pubkey] as `creator_fee_to`.
     ///
-    /// All [OptionsContract]s are call options on the underlying. To write a put option,
-    /// one should invert the quote and underlying.
+    /// Options always have the decimals of the underlying: each option is the right to buy
+    /// (call) or sell (put) one underlying token at the strike. Calls are collateralized by
+    /// the underlying and puts by the strike in quote tokens.
+    ///
+    /// The contract is appended to the [OptionMarket] of its pair as a new [OptionSeries],
+    /// whose address is derived from the market's current [OptionMarket::num_series].
     #[access_control(ctx.accounts.validate())]
+    #[allow(clippy::too_many_arguments)]
     pub fn new_contract(
         ctx: Context<NewContract>,
         strike: u64,
         expiry_ts: i64,
         is_put: bool,
+        exercise_schedule: ExerciseSchedule,
+        is_cash_settled: bool,
         contract_bump: u8,
         crate_bump: u8,
+        exercise_window_secs: u32,
+        creator_fee_kbps: u64,
+        creator_fee_to: Pubkey,
+        series_bump: u8,
+    ) -> ProgramResult {
+        ctx.accounts.new_contract(
+            strike,
+            expiry_ts,
+            is_put,
+            exercise_schedule,
+            is_cash_settled,
+            contract_bump,
+            crate_bump,
+            exercise_window_secs,
+            creator_fee_kbps,
+            creator_fee_to,
+            series_bump,
+        )
+    }
+
+    /// Creates the [OptionMarket] of an underlying/quote pair.
+    ///
+    /// Anyone can create the market. It must exist before contracts on the pair are created.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_option_market(ctx: Context<NewOptionMarket>, bump: u8) -> ProgramResult {
+        ctx.accounts.new_option_market(bump)
+    }
+
+    /// Creates the [MarketParams] of an underlying/quote pair,
+    /// restricting the strikes and expiries of its new contracts.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_market_params(
+        ctx: Context<NewMarketParams>,
+        bump: u8,
+        strike_tick: u64,
+        expiry_period_secs: i64,
+        expiry_offset_secs: i64,
     ) -> ProgramResult {
         ctx.accounts
-            .new_contract(strike, expiry_ts, is_put, contract_bump, crate_bump)
+            .new_market_params(bump, strike_tick, expiry_period_secs, expiry_offset_secs)
+    }
+
+    /// Updates the [MarketParams] of an underlying/quote pair.
+    /// Existing contracts are unaffected.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_market_params(
+        ctx: Context<SetMarketParams>,
+        strike_tick: u64,
+        expiry_period_secs: i64,
+        expiry_offset_secs: i64,
+    ) -> ProgramResult {
+        ctx.accounts
+            .set_market_params(strike_tick, expiry_period_secs, expiry_offset_secs)
     }
 
     /// Write new options
@@ -75,15 +167,128 @@ pub mod traction {
     pub fn option_redeem(ctx: Context<OptionRedeem>, writer_amount: u64) -> ProgramResult {
         ctx.accounts.redeem(writer_amount)
     }
+
+    /// Closes a position before expiry by burning equal amounts of options and writer tokens
+    /// in exchange for the collateral backing them.
+    #[access_control(ctx.accounts.validate())]
+    pub fn option_close(ctx: Context<OptionClose>, close_amount: u64) -> ProgramResult {
+        ctx.accounts.close(close_amount)
+    }
+
+    /// Closes an [OptionsContract] and its [OptionSeries] once the contract has expired and
+    /// every writer token has been redeemed, refunding their rent to the [OptionsContract::payer].
+    ///
+    /// Any tokens left in the writer crate, such as tokens sent to it directly,
+    /// are withdrawn to the token accounts of the payer. The [CrateToken] and its token
+    /// accounts remain open, since Crate Protocol cannot close them.
+    ///
+    /// Contracts with an oracle may only be closed once no outstanding option has
+    /// a settlement value. Anyone can call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_contract(ctx: Context<CloseContract>) -> ProgramResult {
+        ctx.accounts.close_contract()
+    }
+
+    /// Creates the [ProtocolConfig].
+    ///
+    /// Only the upgrade authority of the program may call this, and it becomes the
+    /// [ProtocolConfig::admin]. This should be called immediately after the program is deployed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_protocol_config(
+        ctx: Context<NewProtocolConfig>,
+        bump: u8,
+        treasury: Pubkey,
+    ) -> ProgramResult {
+        ctx.accounts.new_protocol_config(bump, treasury)
+    }
+
+    /// Sweeps the fees accumulated in a [FEE_OWNER] token account to the [ProtocolConfig::treasury].
+    ///
+    /// The treasury is set by the [ProtocolConfig::admin] through [traction::set_protocol_params].
+    /// Anyone can call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn sweep_fees(ctx: Context<SweepFees>) -> ProgramResult {
+        ctx.accounts.sweep_fees()
+    }
+
+    /// Sets the parameters of the [ProtocolConfig].
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_protocol_params(
+        ctx: Context<ProtocolConfigAdmin>,
+        exercise_fee_kbps: u64,
+        treasury: Pubkey,
+        max_tenor_secs: u64,
+    ) -> ProgramResult {
+        ctx.accounts
+            .set_protocol_params(exercise_fee_kbps, treasury, max_tenor_secs)
+    }
+
+    /// Proposes a new [ProtocolConfig::admin].
+    /// The new admin must call [traction::accept_admin] to take effect.
+    #[access_control(ctx.accounts.validate())]
+    pub fn transfer_admin(ctx: Context<ProtocolConfigAdmin>, new_admin: Pubkey) -> ProgramResult {
+        ctx.accounts.transfer_admin(new_admin)
+    }
+
+    /// Accepts the [ProtocolConfig::pending_admin] as the new admin.
+    #[access_control(ctx.accounts.validate())]
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> ProgramResult {
+        ctx.accounts.accept_admin()
+    }
+
+    /// Creates a new [PriceOracle].
+    ///
+    /// Fails unless the program is built with the `test-oracle` feature.
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_price_oracle(ctx: Context<NewPriceOracle>) -> ProgramResult {
+        ctx.accounts.new_price_oracle()
+    }
+
+    /// Publishes a new price to a [PriceOracle].
+    ///
+    /// Fails unless the program is built with the `test-oracle` feature.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_oracle_price(ctx: Context<SetOraclePrice>, price: u64) -> ProgramResult {
+        ctx.accounts.set_oracle_price(price)
+    }
+
+    /// Records the settlement price of an expired [OptionsContract] that has an oracle.
+    ///
+    /// The settlement price is the first oracle price published at or after the
+    /// [OptionsContract::settlement_ts] that is recorded. If the oracle has not yet published
+    /// such a price, this fails and may be retried: the options are never settled
+    /// without a price, so holders keep their intrinsic value however late this is called.
+    ///
+    /// Anyone can call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn settle_contract(ctx: Context<SettleContract>) -> ProgramResult {
+        ctx.accounts.settle_contract()
+    }
+
+    /// Settles options for their intrinsic value at the settlement price, paid in collateral.
+    ///
+    /// This may be called by the holder or by a delegate of the holder's option tokens,
+    /// such as a keeper. The collateral is always sent to the holder.
+    #[access_control(ctx.accounts.validate())]
+    pub fn option_settle(ctx: Context<OptionSettle>, option_amount: u64) -> ProgramResult {
+        ctx.accounts.settle(option_amount)
+    }
 }
 
 /// Accounts for [traction::new_contract].
 #[derive(Accounts)]
 #[instruction(
     strike: u64,
-    expiry_ts: u64,
+    expiry_ts: i64,
     is_put: bool,
-    contract_bump: u8
+    exercise_schedule: ExerciseSchedule,
+    is_cash_settled: bool,
+    contract_bump: u8,
+    crate_bump: u8,
+    exercise_window_secs: u32,
+    creator_fee_kbps: u64,
+    creator_fee_to: Pubkey,
+    series_bump: u8
 )]
 pub struct NewContract<'info> {
     #[account(
@@ -94,7 +299,13 @@ pub struct NewContract<'info> {
             quote_mint.key().to_bytes().as_ref(),
             strike.to_le_bytes().as_ref(),
             expiry_ts.to_le_bytes().as_ref(),
-            (if is_put { &[1_u8] } else { &[0_u8] })
+            (if is_put { &[1_u8] } else { &[0_u8] }),
+            exercise_schedule.to_seed().as_ref(),
+            exercise_window_secs.to_le_bytes().as_ref(),
+            (if is_cash_settled { &[1_u8] } else { &[0_u8] }),
+            oracle.key().to_bytes().as_ref(),
+            creator_fee_kbps.to_le_bytes().as_ref(),
+            creator_fee_to.to_bytes().as_ref()
         ],
         bump = contract_bump,
         payer = payer
@@ -109,7 +320,39 @@ pub struct NewContract<'info> {
     pub writer_crate: WriterCrate<'info>,
     /// The [Mint] of the option instrument.
     pub option_mint: Account<'info, Mint>,
+    /// The oracle providing the settlement price, or the system program if none.
+    pub oracle: UncheckedAccount<'info>,
+    /// The [ProtocolConfig], which defines the maximum tenor.
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// The [OptionMarket] of the underlying/quote pair.
+    #[account(mut)]
+    pub market: Box<Account<'info, OptionMarket>>,
+    /// The [OptionSeries] to create.
+    #[account(
+        init,
+        seeds = [
+            b"OptionSeries" as &[u8],
+            market.key().to_bytes().as_ref(),
+            market.num_series.to_le_bytes().as_ref()
+        ],
+        bump = series_bump,
+        payer = payer
+    )]
+    pub series: Box<Account<'info, OptionSeries>>,
+    /// The [MarketParams] of the underlying/quote pair.
+    /// This account is empty if the pair has no [MarketParams].
+    #[account(
+        seeds = [
+            b"MarketParams" as &[u8],
+            underlying_mint.key().to_bytes().as_ref(),
+            quote_mint.key().to_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub market_params: UncheckedAccount<'info>,
 
+    /// The [OptionsContract::creator].
+    pub creator: Signer<'info>,
     /// Payer to fund accounts.
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -137,6 +380,7 @@ pub struct OptionWrite<'info> {
     #[account(mut)]
     pub writer_authority: Signer<'info>,
     /// The options contract.
+    #[account(mut)]
     pub contract: Box<Account<'info, OptionsContract>>,
     /// The user's collateral tokens used to fund writing the options.
     #[account(mut)]
@@ -173,6 +417,7 @@ pub struct OptionExercise<'info> {
     /// The authority of the [option_token_source] account.
     pub exerciser_authority: Signer<'info>,
     /// The options contract.
+    #[account(mut)]
     pub contract: Box<Account<'info, OptionsContract>>,
 
     /// The [exerciser_authority]'s tokens used to pay for the exercise of the options.
@@ -200,6 +445,12 @@ pub struct OptionExercise<'info> {
     /// The token account to send the exercise fees.
     #[account(mut)]
     pub exercise_fee_destination: Box<Account<'info, TokenAccount>>,
+    /// The [ProtocolConfig], which defines the exercise fee.
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// The token account to send the creator fees.
+    /// Unused if the [OptionsContract] has no creator fee.
+    #[account(mut)]
+    pub creator_fee_destination: Box<Account<'info, TokenAccount>>,
 
     /// Token program.
     pub token_program: Program<'info, Token>,
@@ -214,6 +465,7 @@ pub struct OptionRedeem<'info> {
     #[account(mut)]
     pub writer_authority: Signer<'info>,
     /// The options contract.
+    #[account(mut)]
     pub contract: Box<Account<'info, OptionsContract>>,
 
     /// The writer's writer token account.
@@ -222,6 +474,8 @@ pub struct OptionRedeem<'info> {
     /// The writer mint.
     #[account(mut)]
     pub writer_mint: Box<Account<'info, Mint>>,
+    /// The option mint.
+    pub option_mint: Box<Account<'info, Mint>>,
     /// The underlying token account to send to.
     #[account(mut)]
     pub underlying_token_destination: Box<Account<'info, TokenAccount>>,
@@ -244,6 +498,291 @@ pub struct OptionRedeem<'info> {
     pub crate_token_program: Program<'info, crate_token::program::CrateToken>,
 }
 
+/// Accounts for [traction::option_close].
+#[derive(Accounts)]
+pub struct OptionClose<'info> {
+    /// The authority of the [self::option_token_source] and [self::writer_token_source] accounts.
+    pub closer_authority: Signer<'info>,
+    /// The options contract.
+    #[account(mut)]
+    pub contract: Box<Account<'info, OptionsContract>>,
+
+    /// The option mint.
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    /// The closer's option token account.
+    #[account(mut)]
+    pub option_token_source: Box<Account<'info, TokenAccount>>,
+    /// The writer mint.
+    #[account(mut)]
+    pub writer_mint: Box<Account<'info, Mint>>,
+    /// The closer's writer token account.
+    #[account(mut)]
+    pub writer_token_source: Box<Account<'info, TokenAccount>>,
+
+    /// The writer crate token.
+    pub writer_crate_token: Box<Account<'info, CrateToken>>,
+    /// The writer crate's collateral tokens which collateralize the options.
+    #[account(mut)]
+    pub crate_collateral_tokens: Box<Account<'info, TokenAccount>>,
+    /// The collateral token account to send to.
+    #[account(mut)]
+    pub collateral_token_destination: Box<Account<'info, TokenAccount>>,
+
+    /// Token program.
+    pub token_program: Program<'info, Token>,
+    /// Crate token program.
+    pub crate_token_program: Program<'info, crate_token::program::CrateToken>,
+}
+
+/// Accounts for [traction::close_contract].
+#[derive(Accounts)]
+pub struct CloseContract<'info> {
+    /// The options contract to close.
+    #[account(mut, close = payer)]
+    pub contract: Box<Account<'info, OptionsContract>>,
+    /// The [OptionsContract::series] to close.
+    #[account(mut, close = payer)]
+    pub series: Box<Account<'info, OptionSeries>>,
+    /// The writer mint.
+    pub writer_mint: Box<Account<'info, Mint>>,
+    /// The option mint.
+    pub option_mint: Box<Account<'info, Mint>>,
+
+    /// The writer crate token.
+    pub writer_crate_token: Box<Account<'info, CrateToken>>,
+    /// The [CrateToken]'s tokens which collateralize the options.
+    #[account(mut)]
+    pub crate_collateral_tokens: Box<Account<'info, TokenAccount>>,
+    /// The [CrateToken]'s exercise tokens.
+    #[account(mut)]
+    pub crate_exercise_tokens: Box<Account<'info, TokenAccount>>,
+    /// The payer's token account receiving the collateral left in the crate.
+    #[account(mut)]
+    pub collateral_destination: Box<Account<'info, TokenAccount>>,
+    /// The payer's token account receiving the exercise tokens left in the crate.
+    #[account(mut)]
+    pub exercise_destination: Box<Account<'info, TokenAccount>>,
+
+    /// The [OptionsContract::payer], which receives the rent.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// Token program.
+    pub token_program: Program<'info, Token>,
+    /// Crate token program.
+    pub crate_token_program: Program<'info, crate_token::program::CrateToken>,
+}
+
+/// Accounts for [traction::new_protocol_config].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct NewProtocolConfig<'info> {
+    /// The [ProtocolConfig] to create.
+    #[account(
+        init,
+        seeds = [b"ProtocolConfig" as &[u8]],
+        bump = bump,
+        payer = payer
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// The initial [ProtocolConfig::admin].
+    /// This must be the upgrade authority of the program.
+    pub admin: Signer<'info>,
+    /// The [bpf_loader_upgradeable] program data account of this program.
+    pub program_data: UncheckedAccount<'info>,
+
+    /// Payer to fund accounts.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// System program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [traction::set_protocol_params] and [traction::transfer_admin].
+#[derive(Accounts)]
+pub struct ProtocolConfigAdmin<'info> {
+    /// The [ProtocolConfig].
+    #[account(mut)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// The [ProtocolConfig::admin].
+    pub admin: Signer<'info>,
+}
+
+/// Accounts for [traction::accept_admin].
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The [ProtocolConfig].
+    #[account(mut)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// The [ProtocolConfig::pending_admin].
+    pub pending_admin: Signer<'info>,
+}
+
+/// Accounts for [traction::sweep_fees].
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    /// The [ProtocolConfig].
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// The [FEE_OWNER].
+    pub fee_owner: UncheckedAccount<'info>,
+    /// The [FEE_OWNER]'s token account holding the fees.
+    #[account(mut)]
+    pub fee_tokens: Account<'info, TokenAccount>,
+    /// The [ProtocolConfig::treasury]'s token account to send the fees to.
+    #[account(mut)]
+    pub treasury_tokens: Account<'info, TokenAccount>,
+
+    /// Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for [traction::new_option_market].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct NewOptionMarket<'info> {
+    /// The [OptionMarket] to create.
+    #[account(
+        init,
+        seeds = [
+            b"OptionMarket" as &[u8],
+            underlying_mint.key().to_bytes().as_ref(),
+            quote_mint.key().to_bytes().as_ref()
+        ],
+        bump = bump,
+        payer = payer
+    )]
+    pub market: Account<'info, OptionMarket>,
+    /// [Mint] of the underlying asset.
+    pub underlying_mint: Account<'info, Mint>,
+    /// [Mint] of the quote asset.
+    pub quote_mint: Account<'info, Mint>,
+
+    /// Payer to fund accounts.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// System program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [traction::new_market_params].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct NewMarketParams<'info> {
+    /// The [ProtocolConfig].
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// The [ProtocolConfig::admin].
+    pub admin: Signer<'info>,
+
+    /// The [MarketParams] to create.
+    #[account(
+        init,
+        seeds = [
+            b"MarketParams" as &[u8],
+            underlying_mint.key().to_bytes().as_ref(),
+            quote_mint.key().to_bytes().as_ref()
+        ],
+        bump = bump,
+        payer = payer
+    )]
+    pub market_params: Account<'info, MarketParams>,
+    /// [Mint] of the underlying asset.
+    pub underlying_mint: Account<'info, Mint>,
+    /// [Mint] of the quote asset.
+    pub quote_mint: Account<'info, Mint>,
+
+    /// Payer to fund accounts.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// System program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [traction::set_market_params].
+#[derive(Accounts)]
+pub struct SetMarketParams<'info> {
+    /// The [ProtocolConfig].
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// The [ProtocolConfig::admin].
+    pub admin: Signer<'info>,
+    /// The [MarketParams] to update.
+    #[account(mut)]
+    pub market_params: Account<'info, MarketParams>,
+}
+
+/// Accounts for [traction::new_price_oracle].
+#[derive(Accounts)]
+pub struct NewPriceOracle<'info> {
+    /// The [PriceOracle] to create.
+    #[account(init, payer = payer)]
+    pub oracle: Account<'info, PriceOracle>,
+    /// The [PriceOracle::authority].
+    pub authority: Signer<'info>,
+    /// [Mint] of the underlying asset.
+    pub underlying_mint: Account<'info, Mint>,
+    /// [Mint] of the quote asset.
+    pub quote_mint: Account<'info, Mint>,
+
+    /// Payer to fund accounts.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// System program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [traction::set_oracle_price].
+#[derive(Accounts)]
+pub struct SetOraclePrice<'info> {
+    /// The [PriceOracle].
+    #[account(mut)]
+    pub oracle: Account<'info, PriceOracle>,
+    /// The [PriceOracle::authority].
+    pub authority: Signer<'info>,
+}
+
+/// Accounts for [traction::settle_contract].
+#[derive(Accounts)]
+pub struct SettleContract<'info> {
+    /// The options contract.
+    #[account(mut)]
+    pub contract: Box<Account<'info, OptionsContract>>,
+    /// The [OptionsContract::oracle].
+    pub oracle: UncheckedAccount<'info>,
+}
+
+/// Accounts for [traction::option_settle].
+#[derive(Accounts)]
+pub struct OptionSettle<'info> {
+    /// The owner or delegate of the [self::option_token_source] account.
+    pub settler_authority: Signer<'info>,
+    /// The options contract.
+    #[account(mut)]
+    pub contract: Box<Account<'info, OptionsContract>>,
+
+    /// The option mint.
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    /// The holder's option token account.
+    #[account(mut)]
+    pub option_token_source: Box<Account<'info, TokenAccount>>,
+
+    /// The writer crate token.
+    pub writer_crate_token: Box<Account<'info, CrateToken>>,
+    /// The writer crate's collateral tokens which collateralize the options.
+    #[account(mut)]
+    pub crate_collateral_tokens: Box<Account<'info, TokenAccount>>,
+    /// The holder's collateral token account to send to.
+    #[account(mut)]
+    pub collateral_token_destination: Box<Account<'info, TokenAccount>>,
+
+    /// Token program.
+    pub token_program: Program<'info, Token>,
+    /// Crate token program.
+    pub crate_token_program: Program<'info, crate_token::program::CrateToken>,
+}
+
 /// Error codes.
 #[error]
 pub enum ErrorCode {
@@ -253,7 +792,7 @@ pub enum ErrorCode {
     InsufficientCollateral,
     #[msg("Options contract is expired.")]
     ContractExpired,
-    #[msg("Cannot redeem until contract expiry.")]
+    #[msg("Cannot redeem until the exercise window after contract expiry has closed.")]
     ContractNotYetExpired,
     #[msg("A writer mint must have the same decimals as the underlying.")]
     WriterDecimalMismatch,
@@ -263,6 +802,58 @@ pub enum ErrorCode {
     UselessMints,
     #[msg("Option mint must have zero supply.")]
     OptionMintMustHaveZeroSupply,
+    #[msg("Exercise fee exceeds the maximum.")]
+    ExerciseFeeTooHigh,
+    #[msg("There is no pending admin to accept.")]
+    NoPendingAdmin,
+    #[msg("Creator fee exceeds the maximum.")]
+    CreatorFeeTooHigh,
+    #[msg("Invalid exercise schedule.")]
+    InvalidExerciseSchedule,
+    #[msg("Options may not be exercised outside of an exercise window.")]
+    OutsideExerciseWindow,
+    #[msg("Oracle account is not of a supported type.")]
+    UnsupportedOracle,
+    #[msg("Oracle does not price the underlying and quote of the contract.")]
+    OracleMintMismatch,
+    #[msg("Cash-settled options cannot be exercised; settle them after expiry instead.")]
+    CashSettledContract,
+    #[msg("Options contract has no oracle.")]
+    NoOracle,
+    #[msg("Options contract has already been settled.")]
+    ContractAlreadySettled,
+    #[msg("Options contract has not yet been settled.")]
+    ContractNotSettled,
+    #[msg("Oracle price was published before the contract could be settled.")]
+    StaleSettlementPrice,
+    #[msg("Cannot exercise fewer options than the contract's minimum exercise amount.")]
+    ExerciseAmountTooSmall,
+    #[msg("Exercise payment rounds to zero.")]
+    ZeroExercisePayment,
+    #[msg("Strike must be greater than zero.")]
+    ZeroStrike,
+    #[msg("Expiry must be in the future.")]
+    ExpiryInPast,
+    #[msg("Expiry is further in the future than the maximum tenor.")]
+    TenorTooLong,
+    #[msg("Option market does not match the contract's mints.")]
+    MarketMismatch,
+    #[msg("Strike is not a multiple of the market's strike tick.")]
+    StrikeNotOnTick,
+    #[msg("Expiry is not on the market's expiry grid.")]
+    ExpiryNotOnGrid,
+    #[msg("Expiry period and offset must not be negative.")]
+    InvalidExpiryGrid,
+    #[msg("Cannot close a contract with outstanding writer tokens.")]
+    WriterTokensOutstanding,
+    #[msg("Cannot close a contract with outstanding options that have a settlement value.")]
+    SettlementOutstanding,
+    #[msg("Writer crate does not hold enough collateral for the outstanding options.")]
+    Undercollateralized,
+    #[msg("Contracts without a creator fee must not have a creator fee recipient.")]
+    CreatorFeeRecipientWithoutFee,
+    #[msg("Price oracles for testing are disabled in this build.")]
+    TestOracleDisabled,
 }
 
 #[cfg(test)]
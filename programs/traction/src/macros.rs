@@ -10,7 +10,24 @@ macro_rules! gen_contract_signer_seeds {
             &$contract.strike.to_le_bytes(),
             &$contract.expiry_ts.to_le_bytes(),
             (if $contract.is_put { &[1_u8] } else { &[0_u8] }),
+            &$contract.exercise_schedule.to_seed(),
+            &$contract.exercise_window_secs.to_le_bytes(),
+            (if $contract.is_cash_settled {
+                &[1_u8]
+            } else {
+                &[0_u8]
+            }),
+            &$contract.oracle.to_bytes(),
+            &$contract.creator_fee_kbps.to_le_bytes(),
+            &$contract.creator_fee_to.to_bytes(),
             &[$contract.bump],
         ]]
     };
 }
+
+#[macro_export]
+macro_rules! gen_fee_owner_signer_seeds {
+    () => {
+        &[&[b"TractionDAOFees" as &[u8], &[$crate::FEE_OWNER_BUMP]]]
+    };
+}
@@ -0,0 +1,39 @@
+//! Settlement price oracles.
+//!
+//! Cash-settled [OptionsContract]s read their settlement price through [load_oracle_price],
+//! which dispatches on the owner of the oracle account. Support for additional oracle
+//! programs can be added there.
+//!
+//! The [PriceOracle], whose prices are published by an authority, is only supported
+//! when the program is built with the `test-oracle` feature.
+
+use crate::*;
+
+/// A price read from an oracle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    /// Number of quote tokens for one whole underlying token, denominated like [OptionsContract::strike].
+    pub price: u64,
+    /// When the price was published.
+    pub publish_ts: i64,
+}
+
+/// Loads the latest price of the `underlying_mint` in `quote_mint` from an oracle account.
+#[cfg_attr(not(feature = "test-oracle"), allow(unused_variables))]
+pub fn load_oracle_price(
+    oracle: &AccountInfo,
+    underlying_mint: Pubkey,
+    quote_mint: Pubkey,
+) -> Result<OraclePrice> {
+    #[cfg(feature = "test-oracle")]
+    if *oracle.owner == crate::ID {
+        let oracle: Account<PriceOracle> = Account::try_from(oracle)?;
+        assert_keys_eq!(oracle.underlying_mint, underlying_mint, OracleMintMismatch);
+        assert_keys_eq!(oracle.quote_mint, quote_mint, OracleMintMismatch);
+        return Ok(OraclePrice {
+            price: oracle.price,
+            publish_ts: oracle.publish_ts,
+        });
+    }
+    program_err!(UnsupportedOracle)
+}
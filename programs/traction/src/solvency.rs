@@ -0,0 +1,28 @@
+//! Solvency checks.
+//!
+//! Every instruction that moves collateral or changes the option supply ends with
+//! [assert_collateralized]. The instructions should already keep the crate solvent, so this
+//! is a circuit breaker against bugs in their arithmetic.
+
+use crate::*;
+
+/// Fails if the writer crate does not hold the collateral required for the outstanding options.
+///
+/// The crate collateral tokens and option mint are reloaded, since they are modified by CPIs.
+pub fn assert_collateralized<'info>(
+    contract: &OptionsContract,
+    crate_collateral_tokens: &mut Account<'info, TokenAccount>,
+    option_mint: &mut Account<'info, Mint>,
+) -> ProgramResult {
+    crate_collateral_tokens.reload()?;
+    option_mint.reload()?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let required_collateral =
+        unwrap_int!(contract.calculate_required_collateral(option_mint.supply, now));
+    invariant!(
+        crate_collateral_tokens.amount >= required_collateral,
+        Undercollateralized
+    );
+    Ok(())
+}
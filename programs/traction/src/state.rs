@@ -4,10 +4,84 @@ use num_traits::cast::ToPrimitive;
 
 use crate::*;
 
-/// Number of units of the underlying the strike price is denominated in.
-pub const STRIKE_PRICE_UNITS: u64 = 1_000_000_000;
+/// When an [OptionsContract] may be exercised.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExerciseStyle {
+    /// Exercisable at any time before expiry.
+    American,
+    /// Exercisable only shortly before expiry.
+    European,
+    /// Exercisable shortly before each of a series of dates leading up to expiry.
+    Bermudan,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ExerciseStyle {
+    fn default() -> Self {
+        ExerciseStyle::American
+    }
+}
+
+/// Defines the windows in which an [OptionsContract] may be exercised.
+///
+/// An exercise date is a timestamp at which the option may be exercised;
+/// options may be exercised within the `window_secs` leading up to any exercise date.
+/// - [ExerciseStyle::American] options may be exercised at any time.
+/// - [ExerciseStyle::European] options have a single exercise date: the expiry.
+/// - [ExerciseStyle::Bermudan] options have an exercise date every `period_secs`,
+///   counting backwards from the expiry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExerciseSchedule {
+    /// The style of the option.
+    pub style: ExerciseStyle,
+    /// Number of seconds before each exercise date that exercise is allowed.
+    /// Must be zero for [ExerciseStyle::American] options.
+    pub window_secs: u32,
+    /// Number of seconds between exercise dates.
+    /// Only used by [ExerciseStyle::Bermudan] options.
+    pub period_secs: u32,
+}
+
+impl ExerciseSchedule {
+    /// Returns true if the schedule's parameters are consistent with its style.
+    pub fn is_valid(&self) -> bool {
+        match self.style {
+            ExerciseStyle::American => self.window_secs == 0 && self.period_secs == 0,
+            ExerciseStyle::European => self.window_secs > 0 && self.period_secs == 0,
+            ExerciseStyle::Bermudan => self.window_secs > 0 && self.period_secs > self.window_secs,
+        }
+    }
 
-/// American option
+    /// Returns true if an option expiring at `expiry_ts` may be exercised at `now`.
+    ///
+    /// Options are always exercisable once expired; this does not check
+    /// that the [OptionsContract::exercise_window_secs] after expiry is still open.
+    pub fn is_exercisable_at(&self, now: i64, expiry_ts: i64) -> bool {
+        if now >= expiry_ts {
+            return true;
+        }
+        let secs_until_expiry = expiry_ts - now;
+        match self.style {
+            ExerciseStyle::American => true,
+            ExerciseStyle::European => secs_until_expiry <= self.window_secs.into(),
+            ExerciseStyle::Bermudan => {
+                let secs_until_date = secs_until_expiry.rem_euclid(self.period_secs.into());
+                secs_until_date > 0 && secs_until_date <= self.window_secs.into()
+            }
+        }
+    }
+
+    /// Bytes used to derive the [OptionsContract] address.
+    pub fn to_seed(&self) -> [u8; 9] {
+        let mut seed = [0_u8; 9];
+        seed[0] = self.style as u8;
+        seed[1..5].copy_from_slice(&self.window_secs.to_le_bytes());
+        seed[5..9].copy_from_slice(&self.period_secs.to_le_bytes());
+        seed
+    }
+}
+
+/// An option on the underlying.
 #[account]
 #[derive(Default)]
 pub struct OptionsContract {
@@ -15,14 +89,29 @@ pub struct OptionsContract {
     pub underlying_mint: Pubkey,
     /// Strike price is denominated in this
     pub quote_mint: Pubkey,
-    /// Number of `quote_mint` tokens required to purchase `10^9` of the `underlying_mint`.
+    /// Number of `quote_mint` tokens required to purchase [Self::strike_units]
+    /// of the `underlying_mint`.
     pub strike: u64,
+    /// Number of `underlying_mint` tokens the strike is denominated in.
+    /// This is `10^underlying_decimals`, so the strike is the price of one whole underlying token.
+    pub strike_units: u64,
     /// When the option expires.
     pub expiry_ts: i64,
+    /// Number of seconds after [Self::expiry_ts] that options may still be exercised.
+    /// Writers may only redeem once this window has closed.
+    pub exercise_window_secs: u32,
     /// If the option is a put.
     pub is_put: bool,
+    /// When the option may be exercised.
+    pub exercise_schedule: ExerciseSchedule,
     /// Bump seed.
     pub bump: u8,
+    /// Account which paid the rent of the contract, refunded when the contract is closed.
+    pub payer: Pubkey,
+    /// Account which created the contract.
+    pub creator: Pubkey,
+    /// When the contract was created.
+    pub created_at: i64,
 
     /// The right to receive the proceeds from the option being exercised.
     pub writer_mint: Pubkey,
@@ -34,9 +123,88 @@ pub struct OptionsContract {
     pub crate_exercise_tokens: Pubkey,
     /// The option which can be exercised.
     pub option_mint: Pubkey,
+
+    /// Thousands of BPS of the exercise amount paid to the creator on exercise.
+    pub creator_fee_kbps: u64,
+    /// Owner of the token accounts that receive the creator fee.
+    pub creator_fee_to: Pubkey,
+
+    /// If the option is settled in cash rather than by exercise.
+    pub is_cash_settled: bool,
+    /// The oracle providing the settlement price, or [Pubkey::default] if none.
+    /// Cash-settled options always have an oracle; physically settled options with an oracle
+    /// are automatically exercised when their exercise window closes.
+    pub oracle: Pubkey,
+    /// If the settlement price has been recorded.
+    pub is_settled: bool,
+    /// The settlement price recorded after expiry, denominated like [Self::strike].
+    pub settlement_price: u64,
+
+    /// Total number of options ever written.
+    pub total_written: u64,
+    /// Total number of options ever exercised.
+    pub total_exercised: u64,
+    /// Total number of writer tokens ever redeemed.
+    pub total_redeemed: u64,
+    /// Total number of options ever closed, along with as many writer tokens.
+    pub total_closed: u64,
+    /// Total number of options ever settled.
+    pub total_settled: u64,
+
+    /// The [OptionSeries] of the contract in its [OptionMarket].
+    pub series: Pubkey,
+}
+
+/// Calculates `amount * numerator / denominator`, rounding down.
+fn mul_div_floor(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    (amount as u128)
+        .checked_mul(numerator.into())?
+        .checked_div(denominator.into())?
+        .to_u64()
+}
+
+/// Calculates `amount * numerator / denominator`, rounding up.
+fn mul_div_ceil(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    let denominator: u128 = denominator.into();
+    (amount as u128)
+        .checked_mul(numerator.into())?
+        .checked_add(denominator.checked_sub(1)?)?
+        .checked_div(denominator)?
+        .to_u64()
+}
+
+/// Calculates a fee of `fee_kbps` thousands of BPS on `amount`, rounding down.
+pub fn calculate_fee_kbps(amount: u64, fee_kbps: u64) -> Option<u64> {
+    (amount as u128)
+        .checked_mul(fee_kbps.into())?
+        .checked_div(10_000 * 1_000)?
+        .to_u64()
 }
 
 impl OptionsContract {
+    /// Returns true if the contract has an oracle to settle with.
+    pub fn has_oracle(&self) -> bool {
+        self.oracle != Pubkey::default()
+    }
+
+    /// Timestamp after which the settlement price may be recorded.
+    ///
+    /// Cash-settled options settle at expiry. Physically settled options are automatically
+    /// exercised when the exercise window after expiry closes.
+    /// The settlement price is an oracle price published at or after this time.
+    pub fn settlement_ts(&self) -> Option<i64> {
+        if self.is_cash_settled {
+            Some(self.expiry_ts)
+        } else {
+            self.exercise_deadline_ts()
+        }
+    }
+
+    /// Timestamp at which options may no longer be exercised and writers may redeem.
+    pub fn exercise_deadline_ts(&self) -> Option<i64> {
+        self.expiry_ts.checked_add(self.exercise_window_secs.into())
+    }
+
     /// Mint of the collateral.
     /// If a call, this is the underlying.
     /// If a put, this is the quote.
@@ -57,22 +225,608 @@ impl OptionsContract {
         }
     }
 
-    /// Calculates the number of exercise tokens that correspond
-    /// to the number of options tokens.
-    /// The amount is equal
+    /// Calculates the number of collateral tokens that back `option_amount` options.
+    ///
+    /// This is what is released from the crate when options are exercised or closed.
+    /// Each call is backed by one underlying token; each put by the strike in quote tokens,
+    /// rounded down.
+    pub fn calculate_collateral_amount_for_options(&self, option_amount: u64) -> Option<u64> {
+        if self.is_put {
+            mul_div_floor(option_amount, self.strike, self.strike_units)
+        } else {
+            Some(option_amount)
+        }
+    }
+
+    /// Calculates the number of collateral tokens that must be deposited
+    /// to write `option_amount` options.
+    ///
+    /// This rounds up, so the crate always holds at least
+    /// [Self::calculate_collateral_amount_for_options] for every outstanding option.
+    pub fn calculate_write_collateral_amount_for_options(&self, option_amount: u64) -> Option<u64> {
+        if self.is_put {
+            mul_div_ceil(option_amount, self.strike, self.strike_units)
+        } else {
+            Some(option_amount)
+        }
+    }
+
+    /// Calculates the number of collateral tokens that `option_amount` options
+    /// are worth when cash-settled at `price`.
+    pub fn calculate_settlement_amount_for_options(
+        &self,
+        option_amount: u64,
+        price: u64,
+    ) -> Option<u64> {
+        if self.is_put {
+            // each option is backed by the strike in quote tokens and
+            // worth (strike - price) quote tokens.
+            if price >= self.strike {
+                return Some(0);
+            }
+            mul_div_floor(
+                option_amount,
+                self.strike.checked_sub(price)?,
+                self.strike_units,
+            )
+        } else {
+            // each option is backed by one underlying token and
+            // worth (price - strike) / price underlying tokens.
+            if price <= self.strike {
+                return Some(0);
+            }
+            mul_div_floor(option_amount, price.checked_sub(self.strike)?, price)
+        }
+    }
+
+    /// Calculates the number of exercise tokens that must be paid
+    /// to exercise `option_amount` options.
+    ///
+    /// Each option is the right to buy (call) or sell (put) one underlying token at the strike,
+    /// so exercising a call pays the strike in quote tokens, rounded up, and exercising a put
+    /// pays one underlying token.
     pub fn calculate_exercise_amount_for_options(&self, option_amount: u64) -> Option<u64> {
         if self.is_put {
-            // if underlying is the token to pay the exercise with,
-            // divide by the strike price to get the exercise amount.
-            (option_amount as u128)
-                .checked_mul(STRIKE_PRICE_UNITS.into())?
-                .checked_div(self.strike.into())?
-                .to_u64()
+            Some(option_amount)
         } else {
-            (option_amount as u128)
-                .checked_mul(self.strike.into())?
-                .checked_div(STRIKE_PRICE_UNITS.into())?
-                .to_u64()
+            mul_div_ceil(option_amount, self.strike, self.strike_units)
+        }
+    }
+
+    /// Calculates the number of collateral tokens that the crate must hold at `now`
+    /// for `option_supply` outstanding options.
+    ///
+    /// Options are backed by their full collateral until they can no longer be exercised
+    /// or, if the contract has an oracle, until it is settled. Settled options are only
+    /// owed their settlement value, and expired options without an oracle are owed nothing.
+    pub fn calculate_required_collateral(&self, option_supply: u64, now: i64) -> Option<u64> {
+        if self.is_settled {
+            self.calculate_settlement_amount_for_options(option_supply, self.settlement_price)
+        } else if self.has_oracle() || now < self.exercise_deadline_ts()? {
+            self.calculate_collateral_amount_for_options(option_supply)
+        } else {
+            Some(0)
+        }
+    }
+
+    /// The smallest number of options that may be exercised at once.
+    ///
+    /// This is the number of options whose strike is worth at least one quote token,
+    /// which bounds the rounding on both the exercise payment and the collateral paid out.
+    pub fn min_exercise_amount(&self) -> Option<u64> {
+        Some(mul_div_ceil(1, self.strike_units, self.strike)?.max(1))
+    }
+}
+
+/// Protocol-wide configuration, controlled by the DAO.
+#[account]
+#[derive(Default)]
+pub struct ProtocolConfig {
+    /// Bump seed.
+    pub bump: u8,
+    /// Account which can modify the configuration.
+    pub admin: Pubkey,
+    /// Account which may accept becoming the next [Self::admin].
+    pub pending_admin: Pubkey,
+    /// Owner of the token accounts that protocol fees are swept to.
+    pub treasury: Pubkey,
+    /// Thousands of BPS of the exercise fee.
+    pub exercise_fee_kbps: u64,
+    /// Maximum number of seconds between the creation and expiry of a new [OptionsContract].
+    /// Zero if there is no maximum.
+    pub max_tenor_secs: u64,
+}
+
+/// A price feed whose price is published by its [Self::authority].
+///
+/// This is the simplest oracle supported for cash settlement, for local testing only:
+/// it is only supported when the program is built with the `test-oracle` feature.
+#[account]
+#[derive(Default)]
+pub struct PriceOracle {
+    /// Account which can publish prices.
+    pub authority: Pubkey,
+    /// [Mint] of the asset being priced.
+    pub underlying_mint: Pubkey,
+    /// [Mint] the price is denominated in.
+    pub quote_mint: Pubkey,
+    /// Number of `quote_mint` tokens for one whole `underlying_mint` token,
+    /// denominated like [OptionsContract::strike].
+    pub price: u64,
+    /// When the price was last published.
+    pub publish_ts: i64,
+}
+
+/// Registry of every [OptionsContract] on an underlying/quote pair.
+///
+/// Each contract is recorded in an [OptionSeries] at the next [Self::num_series] index,
+/// so all series of a market may be enumerated by deriving their addresses.
+#[account]
+#[derive(Default)]
+pub struct OptionMarket {
+    /// Bump seed.
+    pub bump: u8,
+    /// [Mint] of the underlying asset.
+    pub underlying_mint: Pubkey,
+    /// [Mint] of the quote asset.
+    pub quote_mint: Pubkey,
+    /// Number of [OptionSeries] in the market.
+    pub num_series: u64,
+}
+
+/// Restricts the strikes and expiries of new [OptionsContract]s on an underlying/quote pair.
+///
+/// Pairs without [MarketParams] accept any strike and expiry.
+#[account]
+#[derive(Default)]
+pub struct MarketParams {
+    /// Bump seed.
+    pub bump: u8,
+    /// [Mint] of the underlying asset.
+    pub underlying_mint: Pubkey,
+    /// [Mint] of the quote asset.
+    pub quote_mint: Pubkey,
+    /// Strikes must be a multiple of this tick. Zero if any strike is allowed.
+    pub strike_tick: u64,
+    /// Expiries must be a multiple of this period after [Self::expiry_offset_secs].
+    /// Zero if any expiry is allowed.
+    pub expiry_period_secs: i64,
+    /// Offset of the expiry grid from the Unix epoch.
+    ///
+    /// For example, weekly expiries on Friday at 08:00 UTC have a period of 604,800 seconds
+    /// and an offset of 115,200 seconds, since the epoch was a Thursday.
+    pub expiry_offset_secs: i64,
+}
+
+impl MarketParams {
+    /// Returns true if the strike lies on the strike grid.
+    pub fn is_valid_strike(&self, strike: u64) -> bool {
+        match strike.checked_rem(self.strike_tick) {
+            Some(remainder) => remainder == 0,
+            // no strike tick
+            None => true,
+        }
+    }
+
+    /// Returns true if the expiry lies on the expiry grid.
+    pub fn is_valid_expiry(&self, expiry_ts: i64) -> bool {
+        if self.expiry_period_secs == 0 {
+            return true;
+        }
+        match expiry_ts.checked_sub(self.expiry_offset_secs) {
+            Some(elapsed) => elapsed.rem_euclid(self.expiry_period_secs) == 0,
+            None => false,
+        }
+    }
+}
+
+/// An entry of an [OptionMarket], pointing to one of its [OptionsContract]s.
+#[account]
+#[derive(Default)]
+pub struct OptionSeries {
+    /// The [OptionMarket].
+    pub market: Pubkey,
+    /// Index of the series in the [OptionMarket].
+    pub index: u64,
+    /// Bump seed.
+    pub bump: u8,
+    /// The [OptionsContract].
+    pub contract: Pubkey,
+    /// [OptionsContract::strike].
+    pub strike: u64,
+    /// [OptionsContract::expiry_ts].
+    pub expiry_ts: i64,
+    /// [OptionsContract::is_put].
+    pub is_put: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Strike units of an underlying with 9 decimals.
+    const STRIKE_UNITS: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_american_exercise_schedule() {
+        let schedule = ExerciseSchedule::default();
+        assert!(schedule.is_valid());
+        assert!(schedule.is_exercisable_at(0, 1_000_000));
+        assert!(schedule.is_exercisable_at(999_999, 1_000_000));
+    }
+
+    #[test]
+    fn test_european_exercise_schedule() {
+        let schedule = ExerciseSchedule {
+            style: ExerciseStyle::European,
+            window_secs: 3_600,
+            period_secs: 0,
+        };
+        assert!(schedule.is_valid());
+        assert!(!schedule.is_exercisable_at(0, 10_000));
+        assert!(!schedule.is_exercisable_at(6_399, 10_000));
+        assert!(schedule.is_exercisable_at(6_400, 10_000));
+        assert!(schedule.is_exercisable_at(9_999, 10_000));
+        assert!(schedule.is_exercisable_at(10_000, 10_000));
+    }
+
+    #[test]
+    fn test_bermudan_exercise_schedule() {
+        let schedule = ExerciseSchedule {
+            style: ExerciseStyle::Bermudan,
+            window_secs: 100,
+            period_secs: 1_000,
+        };
+        assert!(schedule.is_valid());
+        // exercise dates are at 10_000, 9_000, 8_000, ...
+        assert!(schedule.is_exercisable_at(9_999, 10_000));
+        assert!(schedule.is_exercisable_at(9_900, 10_000));
+        assert!(!schedule.is_exercisable_at(9_899, 10_000));
+        assert!(!schedule.is_exercisable_at(9_000, 10_000));
+        assert!(schedule.is_exercisable_at(8_999, 10_000));
+        assert!(schedule.is_exercisable_at(7_900, 10_000));
+        assert!(!schedule.is_exercisable_at(7_500, 10_000));
+        assert!(schedule.is_exercisable_at(10_000, 10_000));
+        assert!(schedule.is_exercisable_at(10_500, 10_000));
+    }
+
+    #[test]
+    fn test_settlement_amount() {
+        let call = OptionsContract {
+            strike: 100 * STRIKE_UNITS,
+            strike_units: STRIKE_UNITS,
+            ..Default::default()
+        };
+        assert_eq!(
+            call.calculate_settlement_amount_for_options(1_000, 80 * STRIKE_UNITS),
+            Some(0)
+        );
+        assert_eq!(
+            call.calculate_settlement_amount_for_options(1_000, 125 * STRIKE_UNITS),
+            Some(200)
+        );
+
+        let put = OptionsContract {
+            strike: 100 * STRIKE_UNITS,
+            strike_units: STRIKE_UNITS,
+            is_put: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            put.calculate_settlement_amount_for_options(1_000, 125 * STRIKE_UNITS),
+            Some(0)
+        );
+        assert_eq!(
+            put.calculate_settlement_amount_for_options(1_000, 80 * STRIKE_UNITS),
+            Some(20_000)
+        );
+    }
+
+    #[test]
+    fn test_required_collateral() {
+        let put = OptionsContract {
+            strike: 100 * STRIKE_UNITS,
+            strike_units: STRIKE_UNITS,
+            is_put: true,
+            expiry_ts: 10_000,
+            exercise_window_secs: 100,
+            ..Default::default()
+        };
+        assert_eq!(
+            put.calculate_required_collateral(1_000, 10_099),
+            Some(100_000)
+        );
+        assert_eq!(put.calculate_required_collateral(1_000, 10_100), Some(0));
+
+        let unsettled = OptionsContract {
+            oracle: Pubkey::new_unique(),
+            ..put.clone()
+        };
+        assert_eq!(
+            unsettled.calculate_required_collateral(1_000, 10_100),
+            Some(100_000)
+        );
+
+        let settled = OptionsContract {
+            is_settled: true,
+            settlement_price: 80 * STRIKE_UNITS,
+            ..unsettled
+        };
+        assert_eq!(
+            settled.calculate_required_collateral(1_000, 10_100),
+            Some(20_000)
+        );
+    }
+
+    #[test]
+    fn test_option_amounts() {
+        let call = OptionsContract {
+            strike: 1_500_000_000,
+            strike_units: STRIKE_UNITS,
+            ..Default::default()
+        };
+        assert_eq!(
+            call.calculate_write_collateral_amount_for_options(3),
+            Some(3)
+        );
+        assert_eq!(call.calculate_collateral_amount_for_options(3), Some(3));
+        assert_eq!(call.calculate_exercise_amount_for_options(3), Some(5));
+        assert_eq!(call.min_exercise_amount(), Some(1));
+
+        let put = OptionsContract {
+            strike: 1_500_000_000,
+            strike_units: STRIKE_UNITS,
+            is_put: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            put.calculate_write_collateral_amount_for_options(3),
+            Some(5)
+        );
+        assert_eq!(put.calculate_collateral_amount_for_options(3), Some(4));
+        assert_eq!(put.calculate_exercise_amount_for_options(3), Some(3));
+        assert_eq!(put.min_exercise_amount(), Some(1));
+    }
+
+    #[test]
+    fn test_strike_units_follow_underlying_decimals() {
+        // 6 decimal underlying struck at 2.5 of a 9 decimal quote
+        let call = OptionsContract {
+            strike: 2_500_000_000,
+            strike_units: 1_000_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            call.calculate_exercise_amount_for_options(1_000_000),
+            Some(2_500_000_000)
+        );
+        assert_eq!(call.calculate_exercise_amount_for_options(1), Some(2_500));
+
+        // 9 decimal underlying struck at 2.5 of a 6 decimal quote
+        let put = OptionsContract {
+            strike: 2_500_000,
+            strike_units: 1_000_000_000,
+            is_put: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            put.calculate_write_collateral_amount_for_options(1_000_000_000),
+            Some(2_500_000)
+        );
+        assert_eq!(
+            put.calculate_collateral_amount_for_options(1_000_000_000),
+            Some(2_500_000)
+        );
+        assert_eq!(
+            put.calculate_write_collateral_amount_for_options(1),
+            Some(1)
+        );
+        assert_eq!(put.calculate_collateral_amount_for_options(1), Some(0));
+    }
+
+    #[test]
+    fn test_min_exercise_amount() {
+        // one option is worth a thousandth of a quote token
+        let call = OptionsContract {
+            strike: 1_000_000,
+            strike_units: STRIKE_UNITS,
+            ..Default::default()
+        };
+        assert_eq!(call.min_exercise_amount(), Some(1_000));
+        assert_eq!(call.calculate_exercise_amount_for_options(1), Some(1));
+        assert_eq!(call.calculate_exercise_amount_for_options(1_000), Some(1));
+        assert_eq!(call.calculate_exercise_amount_for_options(1_001), Some(2));
+
+        let put = OptionsContract {
+            is_put: true,
+            ..call
+        };
+        assert_eq!(put.min_exercise_amount(), Some(1_000));
+        assert_eq!(put.calculate_collateral_amount_for_options(999), Some(0));
+        assert_eq!(put.calculate_collateral_amount_for_options(1_000), Some(1));
+
+        // strikes that are not a divisor of the strike units round up
+        let call = OptionsContract {
+            strike: 3 * STRIKE_UNITS / 2,
+            strike_units: STRIKE_UNITS,
+            ..Default::default()
+        };
+        assert_eq!(call.min_exercise_amount(), Some(1));
+        let call = OptionsContract {
+            strike: 2 * STRIKE_UNITS / 3,
+            strike_units: STRIKE_UNITS,
+            ..Default::default()
+        };
+        assert_eq!(call.min_exercise_amount(), Some(2));
+    }
+
+    #[test]
+    fn test_market_params() {
+        let unrestricted = MarketParams::default();
+        assert!(unrestricted.is_valid_strike(1));
+        assert!(unrestricted.is_valid_expiry(1));
+
+        // weekly on Friday at 08:00 UTC, with strikes in whole quote tokens
+        let weekly = MarketParams {
+            strike_tick: 1_000_000,
+            expiry_period_secs: 604_800,
+            expiry_offset_secs: 115_200,
+            ..Default::default()
+        };
+        assert!(weekly.is_valid_strike(2_000_000));
+        assert!(!weekly.is_valid_strike(2_500_000));
+        assert!(!weekly.is_valid_strike(1));
+        // Friday, January 7, 2022 08:00:00 UTC
+        assert!(weekly.is_valid_expiry(1_641_542_400));
+        assert!(weekly.is_valid_expiry(1_641_542_400 + 604_800));
+        assert!(!weekly.is_valid_expiry(1_641_542_400 + 86_400));
+        assert!(!weekly.is_valid_expiry(1_641_542_401));
+        // grid points before the offset
+        assert!(weekly.is_valid_expiry(115_200 - 604_800));
+        assert!(!weekly.is_valid_expiry(i64::MIN));
+    }
+
+    proptest! {
+        #[test]
+        fn test_write_collateral_covers_options(
+            is_put: bool,
+            strike in 1..1_000_000 * STRIKE_UNITS,
+            option_amount: u32,
+        ) {
+            let contract = OptionsContract {
+                strike,
+                strike_units: STRIKE_UNITS,
+                is_put,
+                ..Default::default()
+            };
+            let option_amount: u64 = option_amount.into();
+            let deposited = contract
+                .calculate_write_collateral_amount_for_options(option_amount)
+                .unwrap();
+            let backing = contract
+                .calculate_collateral_amount_for_options(option_amount)
+                .unwrap();
+            prop_assert!(deposited >= backing);
+            prop_assert!(deposited - backing <= 1);
+        }
+
+        #[test]
+        fn test_rounding_favors_the_crate(
+            is_put: bool,
+            strike in 1..1_000_000 * STRIKE_UNITS,
+            option_amount in 1..u32::MAX as u64,
+        ) {
+            let contract = OptionsContract {
+                strike,
+                strike_units: STRIKE_UNITS,
+                is_put,
+                ..Default::default()
+            };
+            let exact_value = option_amount as u128 * strike as u128;
+            let paid = contract
+                .calculate_exercise_amount_for_options(option_amount)
+                .unwrap();
+            let released = contract
+                .calculate_collateral_amount_for_options(option_amount)
+                .unwrap();
+            if is_put {
+                // exercisers pay exactly the underlying and receive at most the strike
+                prop_assert_eq!(paid, option_amount);
+                prop_assert!(released as u128 * STRIKE_UNITS as u128 <= exact_value);
+            } else {
+                // exercisers pay at least the strike and receive exactly the underlying
+                prop_assert!(paid as u128 * STRIKE_UNITS as u128 >= exact_value);
+                prop_assert_eq!(released, option_amount);
+            }
+            prop_assert!(paid > 0);
+
+            let min_exercise_amount = contract.min_exercise_amount().unwrap();
+            if option_amount >= min_exercise_amount {
+                prop_assert!(released > 0);
+            }
+        }
+
+        #[test]
+        fn test_split_writes_and_exercises_are_solvent(
+            is_put: bool,
+            strike in 1..1_000 * STRIKE_UNITS,
+            writes in proptest::collection::vec(1..1_000_000_u64, 1..8),
+            exercise_fractions in proptest::collection::vec(0..=100_u64, 1..8),
+        ) {
+            let contract = OptionsContract {
+                strike,
+                strike_units: STRIKE_UNITS,
+                is_put,
+                ..Default::default()
+            };
+            let mut collateral: u64 = 0;
+            let mut outstanding: u64 = 0;
+            for write in writes {
+                collateral += contract
+                    .calculate_write_collateral_amount_for_options(write)
+                    .unwrap();
+                outstanding += write;
+            }
+            // exercise the options in arbitrary pieces
+            for fraction in exercise_fractions {
+                let exercised = outstanding * fraction / 100;
+                let released = contract
+                    .calculate_collateral_amount_for_options(exercised)
+                    .unwrap();
+                prop_assert!(released <= collateral);
+                collateral -= released;
+                outstanding -= exercised;
+            }
+            let released = contract
+                .calculate_collateral_amount_for_options(outstanding)
+                .unwrap();
+            prop_assert!(released <= collateral);
+        }
+
+        #[test]
+        fn test_settlement_is_covered_by_collateral(
+            is_put: bool,
+            strike in 1..1_000 * STRIKE_UNITS,
+            price in 1..1_000 * STRIKE_UNITS,
+            option_amount: u32,
+        ) {
+            let contract = OptionsContract {
+                strike,
+                strike_units: STRIKE_UNITS,
+                is_put,
+                ..Default::default()
+            };
+            let option_amount: u64 = option_amount.into();
+            let settlement = contract
+                .calculate_settlement_amount_for_options(option_amount, price)
+                .unwrap();
+            let backing = contract
+                .calculate_collateral_amount_for_options(option_amount)
+                .unwrap();
+            prop_assert!(settlement <= backing);
+        }
+    }
+
+    #[test]
+    fn test_invalid_exercise_schedules() {
+        assert!(!ExerciseSchedule {
+            style: ExerciseStyle::American,
+            window_secs: 1,
+            period_secs: 0,
+        }
+        .is_valid());
+        assert!(!ExerciseSchedule {
+            style: ExerciseStyle::European,
+            window_secs: 0,
+            period_secs: 0,
+        }
+        .is_valid());
+        assert!(!ExerciseSchedule {
+            style: ExerciseStyle::Bermudan,
+            window_secs: 100,
+            period_secs: 100,
         }
+        .is_valid());
     }
 }
@@ -0,0 +1,118 @@
+//! Tests for [traction::traction::option_close].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use traction::{ErrorCode, ExerciseSchedule, OptionsContract};
+
+async fn setup(is_put: bool) -> (TestContext, TestContract, i64) {
+    setup_with_strike(is_put, 2_000_000).await
+}
+
+async fn setup_with_strike(is_put: bool, strike: u64) -> (TestContext, TestContract, i64) {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike,
+            expiry_ts,
+            is_put,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    (test, contract, expiry_ts)
+}
+
+#[tokio::test]
+async fn test_close_releases_collateral() {
+    for is_put in [false, true] {
+        let (mut test, contract, _) = setup(is_put).await;
+        let writer = test.new_writer(&contract, 1_000_000).await;
+
+        test.close(&contract, &writer, 400_000).await.unwrap();
+        assert_eq!(test.token_balance(writer.option_tokens).await, 600_000);
+        assert_eq!(test.token_balance(writer.writer_tokens).await, 600_000);
+        assert_eq!(test.mint_supply(contract.option_mint).await, 600_000);
+        assert_eq!(test.mint_supply(contract.writer_mint).await, 600_000);
+        let data: OptionsContract = test.get_anchor_account(contract.key).await;
+        assert_eq!(data.total_closed, 400_000);
+        let collateral_amount = if is_put { 800_000 } else { 400_000 };
+        assert_eq!(
+            test.token_balance(contract.collateral_tokens(&writer))
+                .await,
+            collateral_amount
+        );
+        assert_eq!(
+            test.token_balance(contract.crate_collateral_tokens).await,
+            collateral_amount * 3 / 2
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_close_put_rounds_collateral_down() {
+    // 3 puts are backed by 5.999997 quote tokens
+    let (mut test, contract, _) = setup_with_strike(true, 1_999_999).await;
+    let writer = test.new_writer(&contract, 3).await;
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        6
+    );
+
+    // writing rounds up and closing rounds down, so the crate keeps the difference
+    test.close(&contract, &writer, 3).await.unwrap();
+    assert_eq!(
+        test.token_balance(contract.collateral_tokens(&writer))
+            .await,
+        5
+    );
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        1
+    );
+    assert_eq!(test.mint_supply(contract.option_mint).await, 0);
+    assert_eq!(test.mint_supply(contract.writer_mint).await, 0);
+}
+
+#[tokio::test]
+async fn test_close_requires_matching_options() {
+    let (mut test, contract, _) = setup(false).await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    let holder = test.new_user(&contract).await;
+    test.transfer(
+        &writer.option_tokens,
+        &holder.option_tokens,
+        &writer.keypair,
+        500_000,
+    )
+    .await
+    .unwrap();
+
+    // the writer no longer holds enough options to close their whole position
+    assert!(test.close(&contract, &writer, 1_000_000).await.is_err());
+    test.close(&contract, &writer, 500_000).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_close_after_expiry_fails() {
+    let (mut test, contract, expiry_ts) = setup(false).await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+
+    test.warp_to(expiry_ts - 1).await;
+    test.close(&contract, &writer, 1).await.unwrap();
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.close(&contract, &writer, 1).await,
+        ErrorCode::ContractExpired,
+    );
+    assert_eq!(test.token_balance(writer.option_tokens).await, 999_999);
+    assert_eq!(test.token_balance(writer.writer_tokens).await, 999_999);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        999_999
+    );
+}
@@ -0,0 +1,150 @@
+//! Tests for [traction::traction::close_contract].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+use traction::{ErrorCode, ExerciseSchedule, OptionMarket, OptionSeries, OptionsContract};
+
+async fn setup() -> (TestContext, TestContract, i64) {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike: 2_000_000,
+            expiry_ts,
+            is_put: false,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    (test, contract, expiry_ts)
+}
+
+#[tokio::test]
+async fn test_close_contract_refunds_rent_to_payer() {
+    let (mut test, contract, expiry_ts) = setup().await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+
+    // cannot close before expiry
+    assert_error(
+        test.close_contract(&contract).await,
+        ErrorCode::ContractNotYetExpired,
+    );
+
+    // cannot close while writer tokens are outstanding
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.close_contract(&contract).await,
+        ErrorCode::WriterTokensOutstanding,
+    );
+
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    // create the token accounts of the payer first, so that only the refund is measured
+    let payer = test.payer().pubkey();
+    test.create_ata(&payer, &contract.collateral_mint).await;
+    test.create_ata(&payer, &contract.exercise_mint).await;
+    let payer_lamports = test.get_account(payer).await.lamports;
+    let reclaimed = test.get_account(contract.key).await.lamports
+        + test.get_account(contract.series).await.lamports;
+    test.close_contract(&contract).await.unwrap();
+
+    assert!(test.try_get_account(contract.key).await.is_none());
+    assert!(test.try_get_account(contract.series).await.is_none());
+    // the payer also pays the transaction fee
+    let refund = test.get_account(payer).await.lamports - payer_lamports;
+    assert!(refund <= reclaimed && refund + 10_000 >= reclaimed);
+}
+
+#[tokio::test]
+async fn test_close_contract_withdraws_crate_dust_to_payer() {
+    let (mut test, contract, expiry_ts) = setup().await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    test.warp_to(expiry_ts).await;
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+
+    // tokens sent to the crate after every writer has redeemed belong to no one
+    test.mint_to(
+        &contract.collateral_mint,
+        &contract.crate_collateral_tokens,
+        7,
+    )
+    .await;
+    test.mint_to(&contract.exercise_mint, &contract.crate_exercise_tokens, 11)
+        .await;
+    test.close_contract(&contract).await.unwrap();
+
+    let payer = test.payer().pubkey();
+    assert_eq!(
+        test.token_balance(spl_associated_token_account::get_associated_token_address(
+            &payer,
+            &contract.collateral_mint
+        ))
+        .await,
+        7
+    );
+    assert_eq!(
+        test.token_balance(spl_associated_token_account::get_associated_token_address(
+            &payer,
+            &contract.exercise_mint
+        ))
+        .await,
+        11
+    );
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        0
+    );
+    assert_eq!(test.token_balance(contract.crate_exercise_tokens).await, 0);
+}
+
+#[tokio::test]
+async fn test_close_contract_closes_series() {
+    let (mut test, contract, expiry_ts) = setup().await;
+    test.warp_to(expiry_ts).await;
+    test.close_contract(&contract).await.unwrap();
+
+    // the closed series leaves a gap in its market, and new series are still appended
+    let market: OptionMarket = test.get_anchor_account(contract.market).await;
+    assert_eq!(market.num_series, 1);
+    assert!(test.try_get_account(contract.series).await.is_none());
+    let recreated = test
+        .try_new_contract_for_mints(
+            contract.underlying_mint,
+            contract.quote_mint,
+            ContractParams {
+                underlying_decimals: 6,
+                quote_decimals: 6,
+                strike: 2_000_000,
+                expiry_ts: expiry_ts + 1_000,
+                is_put: false,
+                exercise_schedule: ExerciseSchedule::default(),
+                exercise_window_secs: 0,
+            },
+        )
+        .await
+        .unwrap();
+    let market: OptionMarket = test.get_anchor_account(contract.market).await;
+    assert_eq!(market.num_series, 2);
+    let series: OptionSeries = test.get_anchor_account(recreated.series).await;
+    assert_eq!(series.contract, recreated.key);
+    assert_eq!(series.index, 1);
+}
+
+#[tokio::test]
+async fn test_close_contract_requires_original_payer() {
+    let (mut test, contract, expiry_ts) = setup().await;
+    test.warp_to(expiry_ts).await;
+
+    let other = test.new_user(&contract).await;
+    // the client derives the destinations from the payer recorded in the contract
+    let mut data: OptionsContract = test.get_anchor_account(contract.key).await;
+    data.payer = other.key();
+    let ix = traction_client::instructions::close_contract(contract.key, &data);
+    assert!(test.process(&[ix], &[]).await.is_err());
+
+    test.close_contract(&contract).await.unwrap();
+}
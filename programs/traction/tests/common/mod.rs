@@ -0,0 +1,953 @@
+//! Shared harness for running Traction against Crate and SPL Token in-process.
+//!
+//! Instructions are built with [traction_client::instructions], so the client is tested
+//! along with the program.
+#![allow(dead_code)]
+
+mod runtime;
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, InstructionData};
+use crate_token::CrateToken;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction::{self, SystemError},
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use traction::{
+    ErrorCode, ExerciseSchedule, MarketParams, OptionMarket, OptionSeries, OptionsContract,
+    PriceOracle, ProtocolConfig, FEE_OWNER,
+};
+pub use traction_client::pda::*;
+use traction_client::{
+    instructions::{self, NewContractArgs},
+    ContractKey,
+};
+
+/// Number of lamports given to each new user.
+const USER_LAMPORTS: u64 = 10_000_000_000;
+
+/// Called with the signature of a transaction processed by [TestContext::process].
+pub type OnProcessed = Box<dyn FnMut(&Signature)>;
+
+/// A running test validator with the Traction and Crate programs loaded.
+pub struct TestContext {
+    pub context: ProgramTestContext,
+    /// Authority of every mint created with [TestContext::create_mint].
+    pub mint_authority: Keypair,
+    /// Called with the signature of each transaction successfully processed
+    /// by [TestContext::process].
+    pub on_processed: Option<OnProcessed>,
+}
+
+/// Parameters of a new [OptionsContract].
+#[derive(Clone, Copy, Debug)]
+pub struct ContractParams {
+    pub underlying_decimals: u8,
+    pub quote_decimals: u8,
+    pub strike: u64,
+    pub expiry_ts: i64,
+    pub is_put: bool,
+    pub exercise_schedule: ExerciseSchedule,
+    pub exercise_window_secs: u32,
+}
+
+/// Less common parameters of a new [OptionsContract], and overrides of the
+/// accounts the harness would otherwise create to be valid.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ContractOptions {
+    /// The [traction::PriceOracle], or [Pubkey::default] if none.
+    pub oracle: Pubkey,
+    pub is_cash_settled: bool,
+    pub creator_fee_kbps: u64,
+    pub creator_fee_to: Pubkey,
+    /// Decimals of the option mint, if not those of the underlying.
+    pub option_decimals: Option<u8>,
+    /// Decimals of the writer mint, if not those of the underlying.
+    pub writer_decimals: Option<u8>,
+    /// Supply of the option mint when the contract is created.
+    pub option_supply: u64,
+    /// The [OptionMarket] to add the contract to, if not that of its pair.
+    pub market: Option<Pubkey>,
+}
+
+/// Addresses of an [OptionsContract] and its accounts.
+#[derive(Clone, Copy, Debug)]
+pub struct TestContract {
+    pub key: Pubkey,
+    pub market: Pubkey,
+    pub series: Pubkey,
+    pub underlying_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub option_mint: Pubkey,
+    pub writer_mint: Pubkey,
+    pub writer_crate: Pubkey,
+    pub crate_collateral_tokens: Pubkey,
+    pub crate_exercise_tokens: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub exercise_mint: Pubkey,
+    /// Token account receiving the creator fee, or the [FEE_OWNER] account if there is none.
+    pub creator_fee_tokens: Pubkey,
+}
+
+/// A user with token accounts for every mint of a [TestContract].
+pub struct TestUser {
+    pub keypair: Keypair,
+    pub underlying_tokens: Pubkey,
+    pub quote_tokens: Pubkey,
+    pub option_tokens: Pubkey,
+    pub writer_tokens: Pubkey,
+}
+
+impl TestUser {
+    pub fn key(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+impl TestContract {
+    /// Token account of `user` holding the collateral mint.
+    pub fn collateral_tokens(&self, user: &TestUser) -> Pubkey {
+        if self.collateral_mint == self.underlying_mint {
+            user.underlying_tokens
+        } else {
+            user.quote_tokens
+        }
+    }
+
+    /// Token account of `user` holding the exercise mint.
+    pub fn exercise_tokens(&self, user: &TestUser) -> Pubkey {
+        if self.exercise_mint == self.underlying_mint {
+            user.underlying_tokens
+        } else {
+            user.quote_tokens
+        }
+    }
+
+    /// [FEE_OWNER] token account receiving exercise fees.
+    pub fn exercise_fee_tokens(&self) -> Pubkey {
+        get_exercise_fee_address(&self.exercise_mint)
+    }
+}
+
+/// Asserts that a transaction failed with a Traction [ErrorCode].
+pub fn assert_error(result: Result<(), TransportError>, error: ErrorCode) {
+    let code = match ProgramError::from(error) {
+        ProgramError::Custom(code) => code,
+        err => panic!("unexpected program error {:?}", err),
+    };
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) if actual == code => {}
+        result => panic!("expected {:?} ({}), got {:?}", error, code, result),
+    }
+}
+
+/// Asserts that a transaction failed with a System program [SystemError].
+pub fn assert_system_error(result: Result<(), TransportError>, error: SystemError) {
+    let code = error.clone() as u32;
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) if actual == code => {}
+        result => panic!("expected {:?} ({}), got {:?}", error, code, result),
+    }
+}
+
+/// Size of an Anchor account, as computed by the `init` constraint.
+pub fn account_space<T: AnchorSerialize + Default>() -> usize {
+    8 + T::default().try_to_vec().unwrap().len()
+}
+
+/// The [ContractKey] of a contract created by the harness.
+pub fn contract_key(
+    underlying_mint: &Pubkey,
+    quote_mint: &Pubkey,
+    params: &ContractParams,
+    options: &ContractOptions,
+) -> ContractKey {
+    ContractKey {
+        underlying_mint: *underlying_mint,
+        quote_mint: *quote_mint,
+        strike: params.strike,
+        expiry_ts: params.expiry_ts,
+        is_put: params.is_put,
+        exercise_schedule: params.exercise_schedule,
+        exercise_window_secs: params.exercise_window_secs,
+        is_cash_settled: options.is_cash_settled,
+        oracle: options.oracle,
+        creator_fee_kbps: options.creator_fee_kbps,
+        creator_fee_to: options.creator_fee_to,
+    }
+}
+
+/// Replaces every reference to the account `from` in an instruction by `to`.
+pub fn replace_account(ix: &mut Instruction, from: &Pubkey, to: &Pubkey) {
+    for meta in ix.accounts.iter_mut().filter(|meta| meta.pubkey == *from) {
+        meta.pubkey = *to;
+    }
+}
+
+impl TestContext {
+    /// Starts a validator with the [ProtocolConfig] created by the context payer.
+    pub async fn new() -> Self {
+        let mut test = Self::start().await;
+        let payer = test.payer();
+        test.new_protocol_config(&payer).await.unwrap();
+        test
+    }
+
+    /// Starts a validator without a [ProtocolConfig].
+    ///
+    /// The context payer is the upgrade authority of the Traction program.
+    pub async fn start() -> Self {
+        let mut program_test =
+            ProgramTest::new("traction", traction::ID, processor!(traction::entry));
+        program_test.add_program(
+            "spl_token",
+            spl_token::ID,
+            processor!(spl_token::processor::Processor::process),
+        );
+        program_test.add_program(
+            "crate_token",
+            crate_token::ID,
+            processor!(crate_token_processor::process_instruction),
+        );
+        let context = program_test.start_with_context().await;
+        let payer_key = context.payer.pubkey();
+        let mut test = TestContext {
+            context,
+            mint_authority: Keypair::new(),
+            on_processed: None,
+        };
+        runtime::install_syscall_stubs();
+        runtime::set_upgrade_authority(&mut test.context, &traction::ID, Some(payer_key)).await;
+        test
+    }
+
+    pub fn payer(&self) -> Keypair {
+        Keypair::from_bytes(&self.context.payer.to_bytes()).unwrap()
+    }
+
+    /// Signs and processes a transaction paid for by the context payer.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransportError> {
+        let payer = self.payer();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        // use a new blockhash so that retried transactions are not deduplicated
+        let recent_blockhash = self
+            .context
+            .banks_client
+            .get_new_latest_blockhash(&self.context.last_blockhash)
+            .await?;
+        self.context.last_blockhash = recent_blockhash;
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+        let signature = tx.signatures[0];
+        self.context.banks_client.process_transaction(tx).await?;
+        if let Some(on_processed) = &mut self.on_processed {
+            on_processed(&signature);
+        }
+        Ok(())
+    }
+
+    pub async fn get_account(&mut self, address: Pubkey) -> Account {
+        self.context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .expect("account not found")
+    }
+
+    /// Fetches an account, if it exists.
+    pub async fn try_get_account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_anchor_account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.get_account(address).await;
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.get_account(address).await;
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn mint_supply(&mut self, mint: Pubkey) -> u64 {
+        let account = self.get_account(mint).await;
+        spl_token::state::Mint::unpack(&account.data)
+            .unwrap()
+            .supply
+    }
+
+    /// Current cluster time.
+    pub async fn now(&mut self) -> i64 {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+            .unix_timestamp
+    }
+
+    /// Moves the cluster clock to `unix_timestamp`.
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let clock = self
+            .context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap();
+        self.context.set_sysvar(&Clock {
+            unix_timestamp,
+            ..clock
+        });
+    }
+
+    /// Creates the [ProtocolConfig] with `admin` as its admin and treasury.
+    pub async fn new_protocol_config(&mut self, admin: &Keypair) -> Result<(), TransportError> {
+        let payer = self.payer();
+        runtime::preallocate(
+            &mut self.context,
+            &find_protocol_config_address().0,
+            account_space::<ProtocolConfig>(),
+            &traction::ID,
+        )
+        .await;
+        let ix = instructions::new_protocol_config(payer.pubkey(), admin.pubkey(), admin.pubkey());
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Sets the [ProtocolConfig] parameters, keeping the context payer as the treasury.
+    pub async fn set_protocol_params(
+        &mut self,
+        exercise_fee_kbps: u64,
+        max_tenor_secs: u64,
+    ) -> Result<(), TransportError> {
+        let payer = self.payer();
+        self.set_protocol_params_as(&payer, exercise_fee_kbps, max_tenor_secs)
+            .await
+    }
+
+    /// Sets the [ProtocolConfig] parameters as `admin`, keeping the context payer as the treasury.
+    pub async fn set_protocol_params_as(
+        &mut self,
+        admin: &Keypair,
+        exercise_fee_kbps: u64,
+        max_tenor_secs: u64,
+    ) -> Result<(), TransportError> {
+        let ix = instructions::set_protocol_params(
+            admin.pubkey(),
+            exercise_fee_kbps,
+            self.payer().pubkey(),
+            max_tenor_secs,
+        );
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Sets the [ProtocolConfig::treasury] as `admin`, keeping the other parameters.
+    pub async fn set_treasury(
+        &mut self,
+        admin: &Keypair,
+        treasury: Pubkey,
+    ) -> Result<(), TransportError> {
+        let config: ProtocolConfig = self
+            .get_anchor_account(find_protocol_config_address().0)
+            .await;
+        let ix = instructions::set_protocol_params(
+            admin.pubkey(),
+            config.exercise_fee_kbps,
+            treasury,
+            config.max_tenor_secs,
+        );
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Proposes `new_admin` as the [ProtocolConfig::admin].
+    pub async fn transfer_admin(
+        &mut self,
+        admin: &Keypair,
+        new_admin: Pubkey,
+    ) -> Result<(), TransportError> {
+        let ix = instructions::transfer_admin(admin.pubkey(), new_admin);
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Accepts the [ProtocolConfig::pending_admin].
+    pub async fn accept_admin(&mut self, pending_admin: &Keypair) -> Result<(), TransportError> {
+        let ix = instructions::accept_admin(pending_admin.pubkey());
+        self.process(&[ix], &[pending_admin]).await
+    }
+
+    /// Sweeps the [FEE_OWNER] tokens of `mint` to `treasury_tokens`.
+    pub async fn sweep_fees(
+        &mut self,
+        mint: &Pubkey,
+        treasury_tokens: Pubkey,
+    ) -> Result<(), TransportError> {
+        let ix = instructions::sweep_fees(mint, treasury_tokens);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Creates a [PriceOracle] of a pair controlled by `authority`.
+    pub async fn new_price_oracle(
+        &mut self,
+        underlying_mint: Pubkey,
+        quote_mint: Pubkey,
+        authority: &Keypair,
+    ) -> Pubkey {
+        let payer = self.payer();
+        let oracle = Keypair::new();
+        runtime::preallocate(
+            &mut self.context,
+            &oracle.pubkey(),
+            account_space::<PriceOracle>(),
+            &traction::ID,
+        )
+        .await;
+        let ix = instructions::new_price_oracle(
+            payer.pubkey(),
+            oracle.pubkey(),
+            authority.pubkey(),
+            underlying_mint,
+            quote_mint,
+        );
+        self.process(&[ix], &[&oracle, authority]).await.unwrap();
+        oracle.pubkey()
+    }
+
+    /// Publishes a price to a [PriceOracle].
+    pub async fn set_oracle_price(
+        &mut self,
+        oracle: Pubkey,
+        authority: &Keypair,
+        price: u64,
+    ) -> Result<(), TransportError> {
+        let ix = instructions::set_oracle_price(oracle, authority.pubkey(), price);
+        self.process(&[ix], &[authority]).await
+    }
+
+    /// Records the settlement price of a contract from its oracle.
+    pub async fn settle_contract(&mut self, contract: &TestContract) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let ix = instructions::settle_contract(contract.key, &data);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Creates a mint with the given authority, also used as the freeze authority.
+    pub async fn create_mint_with_authority(&mut self, authority: &Pubkey, decimals: u8) -> Pubkey {
+        let payer = self.payer();
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::ID,
+                &mint.pubkey(),
+                authority,
+                Some(authority),
+                decimals,
+            )
+            .unwrap(),
+        ];
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates a mint controlled by [TestContext::mint_authority].
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let authority = self.mint_authority.pubkey();
+        self.create_mint_with_authority(&authority, decimals).await
+    }
+
+    /// Creates the associated token account of `owner` for `mint`.
+    ///
+    /// The account is written directly, since the associated token program
+    /// cannot create accounts when run as a native processor.
+    pub async fn create_ata(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let address = get_associated_token_address(owner, mint);
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(
+            spl_token::state::Account {
+                mint: *mint,
+                owner: *owner,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.context.set_account(
+            &address,
+            &Account {
+                lamports: rent.minimum_balance(data.len()),
+                data,
+                owner: spl_token::ID,
+                executable: false,
+                rent_epoch: 0,
+            }
+            .into(),
+        );
+        address
+    }
+
+    /// Overwrites the supply of a mint.
+    pub async fn set_mint_supply(&mut self, mint: &Pubkey, supply: u64) {
+        let mut account = self.get_account(*mint).await;
+        let mut state = spl_token::state::Mint::unpack(&account.data).unwrap();
+        state.supply = supply;
+        spl_token::state::Mint::pack(state, &mut account.data).unwrap();
+        self.context.set_account(mint, &account.into());
+    }
+
+    pub async fn mint_to(&mut self, mint: &Pubkey, destination: &Pubkey, amount: u64) {
+        let authority = Keypair::from_bytes(&self.mint_authority.to_bytes()).unwrap();
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            destination,
+            &authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.process(&[ix], &[&authority]).await.unwrap();
+    }
+
+    /// Creates an [OptionsContract] over two new mints, along with its crate token accounts.
+    pub async fn new_contract(&mut self, params: ContractParams) -> TestContract {
+        self.try_new_contract(params).await.unwrap()
+    }
+
+    /// Attempts to create an [OptionsContract] over two new mints.
+    pub async fn try_new_contract(
+        &mut self,
+        params: ContractParams,
+    ) -> Result<TestContract, TransportError> {
+        let underlying_mint = self.create_mint(params.underlying_decimals).await;
+        let quote_mint = self.create_mint(params.quote_decimals).await;
+        self.try_new_contract_for_mints(underlying_mint, quote_mint, params)
+            .await
+    }
+
+    /// Creates the [traction::OptionMarket] of a pair.
+    pub async fn new_option_market(&mut self, underlying_mint: Pubkey, quote_mint: Pubkey) {
+        self.try_new_option_market(underlying_mint, quote_mint)
+            .await
+            .unwrap();
+    }
+
+    /// Attempts to create the [traction::OptionMarket] of a pair.
+    pub async fn try_new_option_market(
+        &mut self,
+        underlying_mint: Pubkey,
+        quote_mint: Pubkey,
+    ) -> Result<(), TransportError> {
+        let payer = self.payer();
+        runtime::preallocate(
+            &mut self.context,
+            &find_option_market_address(&underlying_mint, &quote_mint).0,
+            account_space::<OptionMarket>(),
+            &traction::ID,
+        )
+        .await;
+        let ix = instructions::new_option_market(payer.pubkey(), underlying_mint, quote_mint);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Creates the [traction::MarketParams] of a pair.
+    pub async fn new_market_params(
+        &mut self,
+        underlying_mint: Pubkey,
+        quote_mint: Pubkey,
+        strike_tick: u64,
+        expiry_period_secs: i64,
+        expiry_offset_secs: i64,
+    ) -> Result<(), TransportError> {
+        let payer = self.payer();
+        runtime::preallocate(
+            &mut self.context,
+            &find_market_params_address(&underlying_mint, &quote_mint).0,
+            account_space::<MarketParams>(),
+            &traction::ID,
+        )
+        .await;
+        let ix = instructions::new_market_params(
+            payer.pubkey(),
+            payer.pubkey(),
+            underlying_mint,
+            quote_mint,
+            strike_tick,
+            expiry_period_secs,
+            expiry_offset_secs,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Updates the [traction::MarketParams] of a pair.
+    pub async fn set_market_params(
+        &mut self,
+        underlying_mint: Pubkey,
+        quote_mint: Pubkey,
+        admin: &Keypair,
+        strike_tick: u64,
+        expiry_period_secs: i64,
+        expiry_offset_secs: i64,
+    ) -> Result<(), TransportError> {
+        let ix = instructions::set_market_params(
+            admin.pubkey(),
+            &underlying_mint,
+            &quote_mint,
+            strike_tick,
+            expiry_period_secs,
+            expiry_offset_secs,
+        );
+        self.process(&[ix], &[admin]).await
+    }
+
+    /// Attempts to create an [OptionsContract] over existing mints,
+    /// creating the [traction::OptionMarket] of the pair if it does not exist.
+    pub async fn try_new_contract_for_mints(
+        &mut self,
+        underlying_mint: Pubkey,
+        quote_mint: Pubkey,
+        params: ContractParams,
+    ) -> Result<TestContract, TransportError> {
+        self.try_new_contract_with_options(
+            underlying_mint,
+            quote_mint,
+            params,
+            ContractOptions::default(),
+        )
+        .await
+    }
+
+    /// Attempts to create an [OptionsContract] over existing mints with [ContractOptions].
+    pub async fn try_new_contract_with_options(
+        &mut self,
+        underlying_mint: Pubkey,
+        quote_mint: Pubkey,
+        params: ContractParams,
+        options: ContractOptions,
+    ) -> Result<TestContract, TransportError> {
+        let payer = self.payer();
+        let key = contract_key(&underlying_mint, &quote_mint, &params, &options);
+        let (pair_market, _) = find_option_market_address(&underlying_mint, &quote_mint);
+        let market = options.market.unwrap_or(pair_market);
+        let num_series = match self.try_get_account(market).await {
+            Some(_) => {
+                let market_data: OptionMarket = self.get_anchor_account(market).await;
+                market_data.num_series
+            }
+            None => {
+                self.new_option_market(underlying_mint, quote_mint).await;
+                0
+            }
+        };
+        let (series, series_bump) = find_option_series_address(&market, num_series);
+
+        let (contract, _) = key.find_address();
+        let option_mint = self
+            .create_mint_with_authority(
+                &contract,
+                options
+                    .option_decimals
+                    .unwrap_or(params.underlying_decimals),
+            )
+            .await;
+        if options.option_supply > 0 {
+            self.set_mint_supply(&option_mint, options.option_supply)
+                .await;
+        }
+        // the crate mint is derived from a new keypair, so create it before the crate
+        let writer_mint_kp = Keypair::new();
+        let (writer_crate, _) = find_writer_crate_address(&writer_mint_kp.pubkey());
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &writer_mint_kp.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::ID,
+                &writer_mint_kp.pubkey(),
+                &writer_crate,
+                Some(&writer_crate),
+                options
+                    .writer_decimals
+                    .unwrap_or(params.underlying_decimals),
+            )
+            .unwrap(),
+        ];
+        self.process(&ixs, &[&writer_mint_kp]).await.unwrap();
+        let writer_mint = writer_mint_kp.pubkey();
+
+        runtime::preallocate(
+            &mut self.context,
+            &contract,
+            account_space::<OptionsContract>(),
+            &traction::ID,
+        )
+        .await;
+        runtime::preallocate(
+            &mut self.context,
+            &writer_crate,
+            account_space::<CrateToken>(),
+            &crate_token::ID,
+        )
+        .await;
+        runtime::preallocate(
+            &mut self.context,
+            &series,
+            account_space::<OptionSeries>(),
+            &traction::ID,
+        )
+        .await;
+
+        let mut ix = instructions::new_contract(&NewContractArgs {
+            key,
+            writer_mint,
+            option_mint,
+            series_index: num_series,
+            creator: payer.pubkey(),
+            payer: payer.pubkey(),
+        });
+        if market != pair_market {
+            replace_account(
+                &mut ix,
+                &find_option_series_address(&pair_market, num_series).0,
+                &series,
+            );
+            replace_account(&mut ix, &pair_market, &market);
+            let mut data =
+                traction::instruction::NewContract::try_from_slice(&ix.data[8..]).unwrap();
+            data.series_bump = series_bump;
+            ix.data = data.data();
+        }
+        self.process(&[ix], &[]).await?;
+
+        let data: OptionsContract = self.get_anchor_account(contract).await;
+        let collateral_mint = data.collateral_mint();
+        let exercise_mint = data.exercise_mint();
+        let crate_collateral_tokens = self.create_ata(&writer_crate, &collateral_mint).await;
+        let crate_exercise_tokens = self.create_ata(&writer_crate, &exercise_mint).await;
+        assert_eq!(crate_collateral_tokens, data.crate_collateral_tokens);
+        assert_eq!(crate_exercise_tokens, data.crate_exercise_tokens);
+        let exercise_fee_tokens = self.create_ata(&FEE_OWNER, &exercise_mint).await;
+        let creator_fee_tokens = if options.creator_fee_kbps > 0 {
+            self.create_ata(&options.creator_fee_to, &exercise_mint)
+                .await
+        } else {
+            exercise_fee_tokens
+        };
+
+        Ok(TestContract {
+            key: contract,
+            market,
+            series,
+            underlying_mint,
+            quote_mint,
+            option_mint,
+            writer_mint,
+            writer_crate,
+            crate_collateral_tokens,
+            crate_exercise_tokens,
+            collateral_mint,
+            exercise_mint,
+            creator_fee_tokens,
+        })
+    }
+
+    /// Creates a funded user with token accounts for every mint of the contract.
+    pub async fn new_user(&mut self, contract: &TestContract) -> TestUser {
+        let payer = self.payer();
+        let keypair = Keypair::new();
+        let ix = system_instruction::transfer(&payer.pubkey(), &keypair.pubkey(), USER_LAMPORTS);
+        self.process(&[ix], &[]).await.unwrap();
+
+        let owner = keypair.pubkey();
+        TestUser {
+            underlying_tokens: self.create_ata(&owner, &contract.underlying_mint).await,
+            quote_tokens: self.create_ata(&owner, &contract.quote_mint).await,
+            option_tokens: self.create_ata(&owner, &contract.option_mint).await,
+            writer_tokens: self.create_ata(&owner, &contract.writer_mint).await,
+            keypair,
+        }
+    }
+
+    /// Creates a user who writes `write_amount` options with newly minted collateral.
+    pub async fn new_writer(&mut self, contract: &TestContract, write_amount: u64) -> TestUser {
+        let writer = self.new_user(contract).await;
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let collateral_amount = data
+            .calculate_write_collateral_amount_for_options(write_amount)
+            .unwrap();
+        let collateral_tokens = contract.collateral_tokens(&writer);
+        self.mint_to(
+            &contract.collateral_mint,
+            &collateral_tokens,
+            collateral_amount,
+        )
+        .await;
+        self.write(contract, &writer, write_amount).await.unwrap();
+        writer
+    }
+
+    pub async fn write(
+        &mut self,
+        contract: &TestContract,
+        writer: &TestUser,
+        write_amount: u64,
+    ) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let ix = instructions::option_write(contract.key, &data, writer.key(), write_amount);
+        self.process(&[ix], &[&writer.keypair]).await
+    }
+
+    /// Transfers tokens owned by `owner`.
+    pub async fn transfer(
+        &mut self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        owner: &Keypair,
+        amount: u64,
+    ) -> Result<(), TransportError> {
+        let ix = spl_token::instruction::transfer(
+            &spl_token::ID,
+            source,
+            destination,
+            &owner.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.process(&[ix], &[owner]).await
+    }
+
+    pub async fn exercise(
+        &mut self,
+        contract: &TestContract,
+        exerciser: &TestUser,
+        option_amount: u64,
+    ) -> Result<(), TransportError> {
+        let collateral_token_destination = contract.collateral_tokens(exerciser);
+        self.exercise_to(
+            contract,
+            exerciser,
+            option_amount,
+            collateral_token_destination,
+        )
+        .await
+    }
+
+    /// Exercises options, sending the collateral to `collateral_token_destination`.
+    pub async fn exercise_to(
+        &mut self,
+        contract: &TestContract,
+        exerciser: &TestUser,
+        option_amount: u64,
+        collateral_token_destination: Pubkey,
+    ) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let mut ix =
+            instructions::option_exercise(contract.key, &data, exerciser.key(), option_amount);
+        replace_account(
+            &mut ix,
+            &contract.collateral_tokens(exerciser),
+            &collateral_token_destination,
+        );
+        self.process(&[ix], &[&exerciser.keypair]).await
+    }
+
+    pub async fn redeem(
+        &mut self,
+        contract: &TestContract,
+        redeemer: &TestUser,
+        writer_amount: u64,
+    ) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let ix = instructions::option_redeem(contract.key, &data, redeemer.key(), writer_amount);
+        self.process(&[ix], &[&redeemer.keypair]).await
+    }
+
+    /// Closes a position before expiry, sending the collateral to the closer.
+    pub async fn close(
+        &mut self,
+        contract: &TestContract,
+        closer: &TestUser,
+        close_amount: u64,
+    ) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let ix = instructions::option_close(contract.key, &data, closer.key(), close_amount);
+        self.process(&[ix], &[&closer.keypair]).await
+    }
+
+    /// Settles the options of `holder`, signed by `settler`.
+    pub async fn settle(
+        &mut self,
+        contract: &TestContract,
+        holder: &TestUser,
+        settler: &Keypair,
+        option_amount: u64,
+    ) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        let ix = instructions::option_settle(
+            contract.key,
+            &data,
+            settler.pubkey(),
+            holder.key(),
+            option_amount,
+        );
+        self.process(&[ix], &[settler]).await
+    }
+
+    /// Closes the contract and its series, refunding their rent to the context payer
+    /// and sending tokens left in the crate to its token accounts, created if needed.
+    pub async fn close_contract(&mut self, contract: &TestContract) -> Result<(), TransportError> {
+        let data: OptionsContract = self.get_anchor_account(contract.key).await;
+        for mint in [data.collateral_mint(), data.exercise_mint()] {
+            let address = get_associated_token_address(&data.payer, &mint);
+            if self.try_get_account(address).await.is_none() {
+                self.create_ata(&data.payer, &mint).await;
+            }
+        }
+        let ix = instructions::close_contract(contract.key, &data);
+        self.process(&[ix], &[]).await
+    }
+}
@@ -0,0 +1,175 @@
+//! Lets Anchor's `init` constraint run under `solana-program-test`'s native processors.
+//!
+//! Native processors cannot resize or reassign accounts through the System program,
+//! so accounts that will be initialized are preallocated by the harness with their final
+//! size and owner. The `allocate` and `assign` instructions Anchor then issues for
+//! those accounts are already satisfied and are skipped while the account is still zeroed.
+//!
+//! Everything else goes through the real System program:
+//!
+//! - preallocated accounts hold a single lamport, so the rent is transferred from the payer
+//!   by Anchor's `init`;
+//! - initializing an account that already holds data reaches `allocate`, which rejects it.
+//!
+//! Only the `create_account` path Anchor takes for accounts without lamports is left
+//! to the TypeScript tests, which run against the BPF build.
+
+use solana_program_test::*;
+use solana_sdk::{
+    account::{AccountSharedData, WritableAccount},
+    account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Epoch,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    system_instruction::SystemInstruction,
+    system_program,
+};
+use std::sync::Once;
+
+/// Stubs used while swapping in [InitSyscallStubs].
+struct DefaultSyscallStubs;
+impl SyscallStubs for DefaultSyscallStubs {}
+
+/// Wraps the `solana-program-test` stubs, skipping System program
+/// instructions that have no effect on a preallocated, uninitialized account.
+struct InitSyscallStubs {
+    inner: Box<dyn SyscallStubs>,
+}
+
+impl InitSyscallStubs {
+    fn is_satisfied(instruction: &Instruction, account_infos: &[AccountInfo]) -> bool {
+        if instruction.program_id != system_program::ID || instruction.accounts.is_empty() {
+            return false;
+        }
+        let target = match account_infos
+            .iter()
+            .find(|info| *info.key == instruction.accounts[0].pubkey)
+        {
+            Some(target) => target,
+            None => return false,
+        };
+        if target.data.borrow().iter().any(|byte| *byte != 0) {
+            return false;
+        }
+        match limited_deserialize(&instruction.data) {
+            Ok(SystemInstruction::Allocate { space }) => target.data_len() as u64 == space,
+            Ok(SystemInstruction::Assign { owner }) => *target.owner == owner,
+            _ => false,
+        }
+    }
+}
+
+impl SyscallStubs for InitSyscallStubs {
+    fn sol_log(&self, message: &str) {
+        self.inner.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.inner.sol_log_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if Self::is_satisfied(instruction, account_infos) {
+            return Ok(());
+        }
+        self.inner
+            .sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.inner.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.inner.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        self.inner.sol_log_data(fields)
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.inner.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.inner.sol_get_stack_height()
+    }
+}
+
+/// Installs [InitSyscallStubs].
+///
+/// Must be called after a [ProgramTest] has started, since starting the first one
+/// installs the stubs being wrapped, and before any transaction is processed.
+pub fn install_syscall_stubs() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let inner = set_syscall_stubs(Box::new(DefaultSyscallStubs));
+        set_syscall_stubs(Box::new(InitSyscallStubs { inner }));
+    });
+}
+
+/// Creates a zeroed account that an `init` constraint can initialize, unless `address`
+/// already exists.
+///
+/// The account holds a single lamport, leaving its rent to the payer of the `init`.
+/// Its rent epoch is in the future so that the bank does not collect it beforehand.
+pub async fn preallocate(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    space: usize,
+    owner: &Pubkey,
+) {
+    if context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .is_some()
+    {
+        return;
+    }
+    let mut account = AccountSharedData::new(1, space, owner);
+    account.set_rent_epoch(Epoch::MAX);
+    context.set_account(address, &account);
+}
+
+/// Creates the program data account that an upgradeable deployment of `program_id` would have.
+///
+/// Programs loaded by [ProgramTest] are not deployed through the upgradeable loader,
+/// so this is needed to test instructions gated on the upgrade authority.
+pub async fn set_upgrade_authority(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    upgrade_authority_address: Option<Pubkey>,
+) {
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+    let state = UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address,
+    };
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let space = UpgradeableLoaderState::programdata_data_offset().unwrap();
+    let account = AccountSharedData::new_data(
+        rent.minimum_balance(space),
+        &state,
+        &bpf_loader_upgradeable::ID,
+    )
+    .unwrap();
+    context.set_account(&program_data, &account);
+}
@@ -0,0 +1,196 @@
+//! Tests for [traction::traction::option_exercise].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use traction::{ErrorCode, ExerciseSchedule, OptionsContract};
+
+/// One underlying token is exercised for 2 quote tokens.
+const STRIKE: u64 = 2_000_000;
+
+async fn setup(is_put: bool) -> (TestContext, TestContract, i64) {
+    setup_with_strike(is_put, STRIKE).await
+}
+
+async fn setup_with_strike(is_put: bool, strike: u64) -> (TestContext, TestContract, i64) {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike,
+            expiry_ts,
+            is_put,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    (test, contract, expiry_ts)
+}
+
+/// Creates a user holding `option_amount` options bought from `writer`,
+/// along with plenty of tokens to exercise them.
+async fn new_holder(
+    test: &mut TestContext,
+    contract: &TestContract,
+    writer: &TestUser,
+    option_amount: u64,
+) -> TestUser {
+    let holder = test.new_user(contract).await;
+    test.transfer(
+        &writer.option_tokens,
+        &holder.option_tokens,
+        &writer.keypair,
+        option_amount,
+    )
+    .await
+    .unwrap();
+    let exercise_tokens = contract.exercise_tokens(&holder);
+    test.mint_to(&contract.exercise_mint, &exercise_tokens, 1_000_000_000)
+        .await;
+    holder
+}
+
+#[tokio::test]
+async fn test_exercise_call() {
+    let (mut test, contract, expiry_ts) = setup(false).await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    let holder = new_holder(&mut test, &contract, &writer, 400_000).await;
+
+    // 400_000 options exercise for 800_000 quote, of which 80 is the exercise fee
+    test.exercise(&contract, &holder, 400_000).await.unwrap();
+    assert_eq!(test.token_balance(holder.underlying_tokens).await, 400_000);
+    assert_eq!(
+        test.token_balance(holder.quote_tokens).await,
+        1_000_000_000 - 800_000
+    );
+    assert_eq!(test.token_balance(holder.option_tokens).await, 0);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        600_000
+    );
+    assert_eq!(
+        test.token_balance(contract.crate_exercise_tokens).await,
+        799_920
+    );
+    assert_eq!(test.token_balance(contract.exercise_fee_tokens()).await, 80);
+    assert_eq!(test.mint_supply(contract.option_mint).await, 600_000);
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.total_written, 1_000_000);
+    assert_eq!(data.total_exercised, 400_000);
+
+    // the writer receives the unexercised underlying and the exercise proceeds
+    test.warp_to(expiry_ts).await;
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 600_000);
+    assert_eq!(test.token_balance(writer.quote_tokens).await, 799_920);
+}
+
+#[tokio::test]
+async fn test_exercise_put() {
+    let (mut test, contract, expiry_ts) = setup(true).await;
+    assert_eq!(contract.collateral_mint, contract.quote_mint);
+    assert_eq!(contract.exercise_mint, contract.underlying_mint);
+
+    // writing 1_000_000 puts locks up the strike in quote tokens
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        2_000_000
+    );
+    assert_eq!(test.token_balance(writer.option_tokens).await, 1_000_000);
+    let holder = new_holder(&mut test, &contract, &writer, 400_000).await;
+
+    // 400_000 options sell 400_000 underlying for 800_000 quote, with a 40 token fee
+    test.exercise(&contract, &holder, 400_000).await.unwrap();
+    assert_eq!(test.token_balance(holder.quote_tokens).await, 800_000);
+    assert_eq!(
+        test.token_balance(holder.underlying_tokens).await,
+        1_000_000_000 - 400_000
+    );
+    assert_eq!(test.token_balance(holder.option_tokens).await, 0);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        1_200_000
+    );
+    assert_eq!(
+        test.token_balance(contract.crate_exercise_tokens).await,
+        399_960
+    );
+    assert_eq!(test.token_balance(contract.exercise_fee_tokens()).await, 40);
+
+    // the writer receives the unexercised quote and the underlying bought
+    test.warp_to(expiry_ts).await;
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.quote_tokens).await, 1_200_000);
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 399_960);
+}
+
+#[tokio::test]
+async fn test_exercise_rejects_wrong_collateral_destination() {
+    for is_put in [false, true] {
+        let (mut test, contract, _) = setup(is_put).await;
+        let writer = test.new_writer(&contract, 1_000_000).await;
+        let holder = new_holder(&mut test, &contract, &writer, 1_000_000).await;
+
+        let wrong_destination = contract.exercise_tokens(&holder);
+        assert!(test
+            .exercise_to(&contract, &holder, 1_000_000, wrong_destination)
+            .await
+            .is_err());
+
+        let destination = contract.collateral_tokens(&holder);
+        test.exercise_to(&contract, &holder, 1_000_000, destination)
+            .await
+            .unwrap();
+        let collateral_amount = if is_put { 2_000_000 } else { 1_000_000 };
+        assert_eq!(test.token_balance(destination).await, collateral_amount);
+    }
+}
+
+#[tokio::test]
+async fn test_exercise_after_expiry_fails() {
+    let (mut test, contract, expiry_ts) = setup(true).await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    let holder = new_holder(&mut test, &contract, &writer, 1_000_000).await;
+
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.exercise(&contract, &holder, 1_000_000).await,
+        ErrorCode::ContractExpired,
+    );
+}
+
+#[tokio::test]
+async fn test_exercise_below_minimum_amount_fails() {
+    // one option exercises for half a quote token, so at least two must be exercised at once
+    for is_put in [false, true] {
+        let (mut test, contract, _) = setup_with_strike(is_put, 500_000).await;
+        let writer = test.new_writer(&contract, 1_000).await;
+        let holder = new_holder(&mut test, &contract, &writer, 1_000).await;
+
+        // exercising nothing pays nothing
+        assert_error(
+            test.exercise(&contract, &holder, 0).await,
+            ErrorCode::ZeroExercisePayment,
+        );
+        assert_error(
+            test.exercise(&contract, &holder, 1).await,
+            ErrorCode::ExerciseAmountTooSmall,
+        );
+
+        test.exercise(&contract, &holder, 3).await.unwrap();
+        let (collateral_amount, exercise_amount) = if is_put { (1, 3) } else { (3, 2) };
+        assert_eq!(
+            test.token_balance(contract.collateral_tokens(&holder))
+                .await,
+            collateral_amount
+        );
+        assert_eq!(
+            test.token_balance(contract.exercise_tokens(&holder)).await,
+            1_000_000_000 - exercise_amount
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Tests of exercise schedules and the exercise window around [traction::OptionsContract::expiry_ts].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use traction::{ErrorCode, ExerciseSchedule, ExerciseStyle};
+
+fn params(
+    expiry_ts: i64,
+    exercise_schedule: ExerciseSchedule,
+    exercise_window_secs: u32,
+) -> ContractParams {
+    ContractParams {
+        underlying_decimals: 6,
+        quote_decimals: 6,
+        strike: 2_000_000,
+        expiry_ts,
+        is_put: false,
+        exercise_schedule,
+        exercise_window_secs,
+    }
+}
+
+/// Creates a contract with a writer and a holder funded to exercise all of its options.
+async fn setup(
+    exercise_schedule: ExerciseSchedule,
+    exercise_window_secs: u32,
+) -> (TestContext, TestContract, TestUser, i64) {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 10_000;
+    let contract = test
+        .new_contract(params(expiry_ts, exercise_schedule, exercise_window_secs))
+        .await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    test.mint_to(&contract.quote_mint, &writer.quote_tokens, 2_000_000)
+        .await;
+    (test, contract, writer, expiry_ts)
+}
+
+#[tokio::test]
+async fn test_invalid_exercise_schedules_are_rejected() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 10_000;
+    for exercise_schedule in [
+        ExerciseSchedule {
+            style: ExerciseStyle::American,
+            window_secs: 100,
+            period_secs: 0,
+        },
+        ExerciseSchedule {
+            style: ExerciseStyle::European,
+            window_secs: 0,
+            period_secs: 0,
+        },
+        ExerciseSchedule {
+            style: ExerciseStyle::Bermudan,
+            window_secs: 100,
+            period_secs: 100,
+        },
+    ] {
+        assert_error(
+            test.try_new_contract(params(expiry_ts, exercise_schedule, 0))
+                .await
+                .map(|_| ()),
+            ErrorCode::InvalidExerciseSchedule,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_european_exercise_window() {
+    let (mut test, contract, writer, expiry_ts) = setup(
+        ExerciseSchedule {
+            style: ExerciseStyle::European,
+            window_secs: 100,
+            period_secs: 0,
+        },
+        0,
+    )
+    .await;
+
+    test.warp_to(expiry_ts - 101).await;
+    assert_error(
+        test.exercise(&contract, &writer, 1).await,
+        ErrorCode::OutsideExerciseWindow,
+    );
+    test.warp_to(expiry_ts - 100).await;
+    test.exercise(&contract, &writer, 1).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_bermudan_exercise_dates() {
+    let (mut test, contract, writer, expiry_ts) = setup(
+        ExerciseSchedule {
+            style: ExerciseStyle::Bermudan,
+            window_secs: 100,
+            period_secs: 1_000,
+        },
+        0,
+    )
+    .await;
+
+    // exercise is allowed in the 100 seconds before every 1_000th second before expiry
+    for (secs_before_expiry, exercisable) in
+        [(1_101, false), (1_100, true), (1_001, true), (1_000, false)]
+    {
+        test.warp_to(expiry_ts - secs_before_expiry).await;
+        let result = test.exercise(&contract, &writer, 1).await;
+        if exercisable {
+            result.unwrap();
+        } else {
+            assert_error(result, ErrorCode::OutsideExerciseWindow);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_exercise_window_after_expiry() {
+    let (mut test, contract, writer, expiry_ts) = setup(ExerciseSchedule::default(), 100).await;
+
+    // options may be exercised until the window closes, and only then redeemed
+    test.warp_to(expiry_ts + 99).await;
+    test.exercise(&contract, &writer, 1).await.unwrap();
+    assert_error(
+        test.redeem(&contract, &writer, 1).await,
+        ErrorCode::ContractNotYetExpired,
+    );
+    assert_error(
+        test.close_contract(&contract).await,
+        ErrorCode::ContractNotYetExpired,
+    );
+
+    test.warp_to(expiry_ts + 100).await;
+    assert_error(
+        test.exercise(&contract, &writer, 1).await,
+        ErrorCode::ContractExpired,
+    );
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    test.close_contract(&contract).await.unwrap();
+}
@@ -0,0 +1,624 @@
+//! Stateful fuzzing of instruction sequences against an in-process bank.
+//!
+//! Each case creates a contract with random decimals, strike, expiry, exercise schedule,
+//! fees and settlement: physical, physical with an oracle, or cash. It then runs a random
+//! sequence of writes, exercises, closes, redeems, option transfers, oracle prices,
+//! settlements and clock jumps by a few users. Instructions are allowed to fail; the
+//! invariants are checked after every step, and again once every holder has settled
+//! and every writer has redeemed.
+//!
+//! Set `PROPTEST_CASES` to run more cases.
+
+mod common;
+
+use common::*;
+use proptest::prelude::*;
+use solana_program_test::tokio;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use traction::{
+    ExerciseSchedule, ExerciseStyle, OptionsContract, MAX_CREATOR_FEE_KBPS, MAX_EXERCISE_FEE_KBPS,
+};
+
+/// Number of users taking part in each sequence.
+const USERS: usize = 3;
+
+/// Number of cases run when `PROPTEST_CASES` is not set.
+const DEFAULT_CASES: u32 = 32;
+
+/// How the options of a contract are settled.
+#[derive(Clone, Copy, Debug)]
+enum Settlement {
+    /// Exercised by their holders, without an oracle.
+    Physical,
+    /// Exercised by their holders, and automatically exercised with an oracle.
+    PhysicalWithOracle,
+    /// Settled in the collateral at the price of an oracle.
+    Cash,
+}
+
+#[derive(Clone, Debug)]
+struct Setup {
+    underlying_decimals: u8,
+    quote_decimals: u8,
+    strike: u64,
+    is_put: bool,
+    expiry_secs: i64,
+    exercise_schedule: ExerciseSchedule,
+    exercise_window_secs: u32,
+    exercise_fee_kbps: u64,
+    creator_fee_kbps: u64,
+    settlement: Settlement,
+}
+
+/// A step of a sequence.
+///
+/// Amounts of tokens the user already holds are taken modulo their balance,
+/// so that most steps operate on a position that exists.
+#[derive(Clone, Debug)]
+enum Action {
+    Write {
+        user: usize,
+        amount: u64,
+    },
+    Exercise {
+        user: usize,
+        amount: u64,
+    },
+    Close {
+        user: usize,
+        amount: u64,
+    },
+    Redeem {
+        user: usize,
+        amount: u64,
+    },
+    TransferOptions {
+        from: usize,
+        to: usize,
+        amount: u64,
+    },
+    /// Publishes a price of `percent` of the strike.
+    SetPrice {
+        percent: u64,
+    },
+    SettleContract,
+    Settle {
+        user: usize,
+        amount: u64,
+    },
+    Warp {
+        secs: i64,
+    },
+}
+
+fn schedule_strategy() -> impl Strategy<Value = ExerciseSchedule> {
+    prop_oneof![
+        Just(ExerciseSchedule::default()),
+        (1..1_000_u32).prop_map(|window_secs| ExerciseSchedule {
+            style: ExerciseStyle::European,
+            window_secs,
+            period_secs: 0,
+        }),
+        (1..300_u32, 1..700_u32).prop_map(|(window_secs, gap_secs)| ExerciseSchedule {
+            style: ExerciseStyle::Bermudan,
+            window_secs,
+            period_secs: window_secs + gap_secs,
+        }),
+    ]
+}
+
+fn settlement_strategy() -> impl Strategy<Value = Settlement> {
+    prop_oneof![
+        Just(Settlement::Physical),
+        Just(Settlement::PhysicalWithOracle),
+        Just(Settlement::Cash),
+    ]
+}
+
+fn setup_strategy() -> impl Strategy<Value = Setup> {
+    (
+        0..=9_u8,
+        0..=9_u8,
+        1..100_000_000_u64,
+        any::<bool>(),
+        1..2_000_i64,
+        schedule_strategy(),
+        0..1_000_u32,
+        0..=MAX_EXERCISE_FEE_KBPS,
+        prop_oneof![Just(0), 0..=MAX_CREATOR_FEE_KBPS],
+        settlement_strategy(),
+    )
+        .prop_map(
+            |(
+                underlying_decimals,
+                quote_decimals,
+                strike,
+                is_put,
+                expiry_secs,
+                exercise_schedule,
+                exercise_window_secs,
+                exercise_fee_kbps,
+                creator_fee_kbps,
+                settlement,
+            )| Setup {
+                underlying_decimals,
+                quote_decimals,
+                strike,
+                is_put,
+                expiry_secs,
+                // cash-settled options are only settled at expiry
+                exercise_schedule: match settlement {
+                    Settlement::Cash => ExerciseSchedule::default(),
+                    _ => exercise_schedule,
+                },
+                exercise_window_secs,
+                exercise_fee_kbps,
+                creator_fee_kbps,
+                settlement,
+            },
+        )
+}
+
+fn amount_strategy() -> impl Strategy<Value = u64> {
+    // small amounts exercise the rounding, large ones the overflow checks
+    prop_oneof![1..1_000_u64, 1..1_000_000_000_u64]
+}
+
+fn action_strategy() -> impl Strategy<Value = Action> {
+    let user = 0..USERS;
+    prop_oneof![
+        3 => (user.clone(), amount_strategy()).prop_map(|(user, amount)| Action::Write { user, amount }),
+        3 => (user.clone(), amount_strategy()).prop_map(|(user, amount)| Action::Exercise { user, amount }),
+        1 => (user.clone(), amount_strategy()).prop_map(|(user, amount)| Action::Close { user, amount }),
+        1 => (user.clone(), amount_strategy()).prop_map(|(user, amount)| Action::Redeem { user, amount }),
+        2 => (user.clone(), user.clone(), amount_strategy())
+            .prop_map(|(from, to, amount)| Action::TransferOptions { from, to, amount }),
+        1 => (1..200_u64).prop_map(|percent| Action::SetPrice { percent }),
+        1 => Just(Action::SettleContract),
+        1 => (user, amount_strategy()).prop_map(|(user, amount)| Action::Settle { user, amount }),
+        2 => (0..600_i64).prop_map(|secs| Action::Warp { secs }),
+        // rarely skip far past expiry, delaying the settlement
+        1 => (0..30 * 24 * 60 * 60_i64).prop_map(|secs| Action::Warp { secs }),
+    ]
+}
+
+/// Takes `amount` modulo a nonzero `balance`.
+fn clamp(amount: u64, balance: u64) -> u64 {
+    if balance == 0 {
+        amount
+    } else {
+        1 + amount % balance
+    }
+}
+
+/// A contract, its users, and the tokens minted to each user.
+struct Fuzzer {
+    test: TestContext,
+    contract: TestContract,
+    users: Vec<TestUser>,
+    /// Authority of the oracle of the contract, if any.
+    oracle_authority: Keypair,
+    /// Underlying and quote tokens minted to each user.
+    minted: Vec<(u64, u64)>,
+    /// Number of steps processed, bounding the rounding dust in the crate.
+    steps: u64,
+}
+
+impl Fuzzer {
+    async fn new(setup: &Setup) -> Self {
+        let mut test = TestContext::new().await;
+        test.set_protocol_params(setup.exercise_fee_kbps, 0)
+            .await
+            .unwrap();
+        let underlying_mint = test.create_mint(setup.underlying_decimals).await;
+        let quote_mint = test.create_mint(setup.quote_decimals).await;
+        let oracle_authority = Keypair::new();
+        let oracle = match setup.settlement {
+            Settlement::Physical => Pubkey::default(),
+            Settlement::PhysicalWithOracle | Settlement::Cash => {
+                test.new_price_oracle(underlying_mint, quote_mint, &oracle_authority)
+                    .await
+            }
+        };
+        let creator_fee_to = if setup.creator_fee_kbps > 0 {
+            Keypair::new().pubkey()
+        } else {
+            Pubkey::default()
+        };
+        let expiry_ts = test.now().await + setup.expiry_secs;
+        let contract = test
+            .try_new_contract_with_options(
+                underlying_mint,
+                quote_mint,
+                ContractParams {
+                    underlying_decimals: setup.underlying_decimals,
+                    quote_decimals: setup.quote_decimals,
+                    strike: setup.strike,
+                    expiry_ts,
+                    is_put: setup.is_put,
+                    exercise_schedule: setup.exercise_schedule,
+                    exercise_window_secs: setup.exercise_window_secs,
+                },
+                ContractOptions {
+                    oracle,
+                    is_cash_settled: matches!(setup.settlement, Settlement::Cash),
+                    creator_fee_kbps: setup.creator_fee_kbps,
+                    creator_fee_to,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let mut users = vec![];
+        for _ in 0..USERS {
+            users.push(test.new_user(&contract).await);
+        }
+        Fuzzer {
+            test,
+            contract,
+            users,
+            oracle_authority,
+            minted: vec![(0, 0); USERS],
+            steps: 0,
+        }
+    }
+
+    async fn data(&mut self) -> OptionsContract {
+        self.test.get_anchor_account(self.contract.key).await
+    }
+
+    /// Mints `amount` of `mint` to the matching token account of a user.
+    async fn fund(&mut self, user: usize, mint: Pubkey, amount: u64) {
+        let (underlying, quote) = &mut self.minted[user];
+        let destination = if mint == self.contract.underlying_mint {
+            *underlying += amount;
+            self.users[user].underlying_tokens
+        } else {
+            *quote += amount;
+            self.users[user].quote_tokens
+        };
+        self.test.mint_to(&mint, &destination, amount).await;
+    }
+
+    async fn step(&mut self, action: &Action) {
+        let data = self.data().await;
+        // results are ignored: the invariants must hold whether or not a step succeeds
+        match *action {
+            Action::Write { user, amount } => {
+                if let Some(collateral) = data.calculate_write_collateral_amount_for_options(amount)
+                {
+                    self.fund(user, data.collateral_mint(), collateral).await;
+                }
+                let _ = self
+                    .test
+                    .write(&self.contract, &self.users[user], amount)
+                    .await;
+            }
+            Action::Exercise { user, amount } => {
+                let options = self
+                    .test
+                    .token_balance(self.users[user].option_tokens)
+                    .await;
+                let amount = clamp(amount, options);
+                if let Some(payment) = data.calculate_exercise_amount_for_options(amount) {
+                    self.fund(user, data.exercise_mint(), payment).await;
+                }
+                let _ = self
+                    .test
+                    .exercise(&self.contract, &self.users[user], amount)
+                    .await;
+            }
+            Action::Close { user, amount } => {
+                let options = self
+                    .test
+                    .token_balance(self.users[user].option_tokens)
+                    .await;
+                let _ = self
+                    .test
+                    .close(&self.contract, &self.users[user], clamp(amount, options))
+                    .await;
+            }
+            Action::Redeem { user, amount } => {
+                let writer_tokens = self
+                    .test
+                    .token_balance(self.users[user].writer_tokens)
+                    .await;
+                let _ = self
+                    .test
+                    .redeem(
+                        &self.contract,
+                        &self.users[user],
+                        clamp(amount, writer_tokens),
+                    )
+                    .await;
+            }
+            Action::TransferOptions { from, to, amount } => {
+                let options = self
+                    .test
+                    .token_balance(self.users[from].option_tokens)
+                    .await;
+                let _ = self
+                    .test
+                    .transfer(
+                        &self.users[from].option_tokens,
+                        &self.users[to].option_tokens,
+                        &self.users[from].keypair,
+                        clamp(amount, options),
+                    )
+                    .await;
+            }
+            Action::SetPrice { percent } => {
+                if data.has_oracle() {
+                    let price = (u128::from(data.strike) * u128::from(percent) / 100).max(1);
+                    let _ = self
+                        .test
+                        .set_oracle_price(data.oracle, &self.oracle_authority, price as u64)
+                        .await;
+                }
+            }
+            Action::SettleContract => {
+                let _ = self.test.settle_contract(&self.contract).await;
+            }
+            Action::Settle { user, amount } => {
+                let options = self
+                    .test
+                    .token_balance(self.users[user].option_tokens)
+                    .await;
+                let _ = self
+                    .test
+                    .settle(
+                        &self.contract,
+                        &self.users[user],
+                        &self.users[user].keypair,
+                        clamp(amount, options),
+                    )
+                    .await;
+            }
+            Action::Warp { secs } => {
+                let now = self.test.now().await;
+                self.test.warp_to(now + secs).await;
+            }
+        }
+        self.steps += 1;
+    }
+
+    /// Value of underlying and quote amounts at the strike, in quote tokens times the strike units.
+    fn value(data: &OptionsContract, underlying: i128, quote: i128) -> i128 {
+        underlying * i128::from(data.strike) + quote * i128::from(data.strike_units)
+    }
+
+    async fn check_invariants(&mut self) {
+        let data = self.data().await;
+        let now = self.test.now().await;
+        let option_supply = self.test.mint_supply(self.contract.option_mint).await;
+        let writer_supply = self.test.mint_supply(self.contract.writer_mint).await;
+        let crate_collateral = self
+            .test
+            .token_balance(self.contract.crate_collateral_tokens)
+            .await;
+        let crate_exercise = self
+            .test
+            .token_balance(self.contract.crate_exercise_tokens)
+            .await;
+
+        // crate collateral always covers outstanding options while they can be exercised
+        if now < data.exercise_deadline_ts().unwrap() {
+            let backing = data
+                .calculate_collateral_amount_for_options(option_supply)
+                .unwrap();
+            assert!(
+                crate_collateral >= backing,
+                "crate holds {} collateral for {} options, which need {}",
+                crate_collateral,
+                option_supply,
+                backing
+            );
+            assert!(option_supply <= writer_supply);
+        }
+
+        // settled options are owed at most their collateral, which the crate still holds
+        if data.is_settled {
+            let owed = data
+                .calculate_settlement_amount_for_options(option_supply, data.settlement_price)
+                .unwrap();
+            assert!(
+                owed <= data
+                    .calculate_collateral_amount_for_options(option_supply)
+                    .unwrap()
+            );
+            assert!(
+                crate_collateral >= owed,
+                "crate holds {} collateral for {} settled options, which are owed {}",
+                crate_collateral,
+                option_supply,
+                owed
+            );
+        }
+
+        // the counters of the contract reconcile with the supply of its mints
+        assert_eq!(
+            option_supply,
+            data.total_written - data.total_exercised - data.total_closed - data.total_settled
+        );
+        assert_eq!(
+            writer_supply,
+            data.total_written - data.total_closed - data.total_redeemed
+        );
+
+        // tokens are only moved, never created
+        let mut fees = self
+            .test
+            .token_balance(self.contract.exercise_fee_tokens())
+            .await;
+        if data.creator_fee_kbps > 0 {
+            fees += self
+                .test
+                .token_balance(self.contract.creator_fee_tokens)
+                .await;
+        }
+        let (crate_underlying, crate_quote, fee_underlying, fee_quote) = if data.is_put {
+            (crate_exercise, crate_collateral, fees, 0)
+        } else {
+            (crate_collateral, crate_exercise, 0, fees)
+        };
+        let mut users_underlying = 0;
+        let mut users_quote = 0;
+        for user in &self.users {
+            users_underlying += self.test.token_balance(user.underlying_tokens).await;
+            users_quote += self.test.token_balance(user.quote_tokens).await;
+        }
+        assert_eq!(
+            users_underlying + crate_underlying + fee_underlying,
+            self.test.mint_supply(self.contract.underlying_mint).await
+        );
+        assert_eq!(
+            users_quote + crate_quote + fee_quote,
+            self.test.mint_supply(self.contract.quote_mint).await
+        );
+
+        // options settled in the money move value from the writers to the holders,
+        // which may have received their options for free
+        let settled_in_the_money = data.is_settled
+            && if data.is_put {
+                data.settlement_price < data.strike
+            } else {
+                data.settlement_price > data.strike
+            };
+        if settled_in_the_money {
+            return;
+        }
+
+        // no user extracts more than they deposited, up to the rounding kept by the crate
+        // on each step, which may be redeemed by any writer
+        let dust = i128::from(self.steps + 1) * Self::value(&data, 1, 1);
+        for (user, (minted_underlying, minted_quote)) in self.users.iter().zip(&self.minted) {
+            let underlying = self.test.token_balance(user.underlying_tokens).await;
+            let quote = self.test.token_balance(user.quote_tokens).await;
+            let extracted = Self::value(
+                &data,
+                i128::from(underlying) - i128::from(*minted_underlying),
+                i128::from(quote) - i128::from(*minted_quote),
+            );
+            assert!(
+                extracted <= dust,
+                "user extracted {} more than deposited, above {} of dust",
+                extracted,
+                dust
+            );
+        }
+    }
+
+    /// Settles the contract and every option if it has an oracle,
+    /// then redeems every writer token after the exercise deadline.
+    async fn redeem_all(&mut self) {
+        let data = self.data().await;
+        let now = self.test.now().await;
+        let deadline = data.exercise_deadline_ts().unwrap();
+        if now < deadline {
+            self.test.warp_to(deadline).await;
+        }
+        if data.has_oracle() {
+            self.settle_all().await;
+        }
+        for user in 0..USERS {
+            let writer_tokens = self
+                .test
+                .token_balance(self.users[user].writer_tokens)
+                .await;
+            if writer_tokens > 0 {
+                self.test
+                    .redeem(&self.contract, &self.users[user], writer_tokens)
+                    .await
+                    .unwrap();
+                self.steps += 1;
+            }
+            self.check_invariants().await;
+        }
+
+        // the last redeemer receives everything left in the crate
+        assert_eq!(self.test.mint_supply(self.contract.writer_mint).await, 0);
+        assert_eq!(
+            self.test
+                .token_balance(self.contract.crate_collateral_tokens)
+                .await,
+            0
+        );
+        assert_eq!(
+            self.test
+                .token_balance(self.contract.crate_exercise_tokens)
+                .await,
+            0
+        );
+        self.test.close_contract(&self.contract).await.unwrap();
+    }
+
+    /// Settles the contract, publishing a price at its strike if the oracle has no usable
+    /// price, then settles the options of every user.
+    async fn settle_all(&mut self) {
+        let data = self.data().await;
+        if !data.is_settled && self.test.settle_contract(&self.contract).await.is_err() {
+            self.test
+                .set_oracle_price(data.oracle, &self.oracle_authority, data.strike)
+                .await
+                .unwrap();
+            self.test.settle_contract(&self.contract).await.unwrap();
+        }
+        self.steps += 1;
+        self.check_invariants().await;
+
+        for user in 0..USERS {
+            let options = self
+                .test
+                .token_balance(self.users[user].option_tokens)
+                .await;
+            if options > 0 {
+                self.test
+                    .settle(
+                        &self.contract,
+                        &self.users[user],
+                        &self.users[user].keypair,
+                        options,
+                    )
+                    .await
+                    .unwrap();
+                self.steps += 1;
+            }
+            self.check_invariants().await;
+        }
+        assert_eq!(self.test.mint_supply(self.contract.option_mint).await, 0);
+    }
+}
+
+async fn run(setup: Setup, actions: Vec<Action>) {
+    let mut fuzzer = Fuzzer::new(&setup).await;
+    for action in &actions {
+        fuzzer.step(action).await;
+        fuzzer.check_invariants().await;
+    }
+    fuzzer.redeem_all().await;
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: std::env::var("PROPTEST_CASES")
+            .ok()
+            .and_then(|cases| cases.parse().ok())
+            .unwrap_or(DEFAULT_CASES),
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn test_instruction_sequences(
+        setup in setup_strategy(),
+        actions in proptest::collection::vec(action_strategy(), 1..24),
+    ) {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run(setup, actions));
+    }
+}
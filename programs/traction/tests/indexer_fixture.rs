@@ -0,0 +1,158 @@
+//! Records the transactions used as a fixture by the `traction-indexer` tests.
+//!
+//! The program is run in-process, so its log messages are captured from the
+//! `stable_log` output of the runtime rather than fetched from a cluster. Rerun this
+//! whenever an event changes:
+//!
+//! ```sh
+//! cargo test -p traction --test indexer_fixture -- --ignored
+//! ```
+
+mod common;
+
+use common::*;
+use serde_json::{json, Value};
+use solana_program_test::tokio;
+use solana_sdk::{
+    clock::Slot,
+    signature::{Signature, Signer},
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+use traction::{ExerciseSchedule, OptionsContract};
+
+/// Where the fixture is written.
+const FIXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../crates/traction-indexer/tests/fixtures/transactions.json"
+);
+
+/// Target of the log messages returned by `getTransaction` on a cluster.
+const STABLE_LOG_TARGET: &str = "solana_runtime::message_processor::stable_log";
+
+/// Log messages recorded since they were last taken.
+static LOGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Transactions of the Traction program recorded so far.
+static TRANSACTIONS: Mutex<Vec<Value>> = Mutex::new(vec![]);
+
+/// Slot of the transactions being processed.
+static SLOT: AtomicU64 = AtomicU64::new(0);
+
+/// Records the [STABLE_LOG_TARGET] messages into [LOGS].
+struct StableLogRecorder;
+
+impl log::Log for StableLogRecorder {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == STABLE_LOG_TARGET
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            LOGS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: StableLogRecorder = StableLogRecorder;
+
+fn take_logs() -> Vec<String> {
+    std::mem::take(&mut *LOGS.lock().unwrap())
+}
+
+/// Records the transaction that was just processed into [TRANSACTIONS],
+/// if it invoked the Traction program.
+fn record_transaction(signature: &Signature) {
+    let logs = take_logs();
+    let invoke = format!("Program {} invoke", traction::ID);
+    if logs.iter().any(|log| log.starts_with(&invoke)) {
+        TRANSACTIONS.lock().unwrap().push(json!({
+            "slot": SLOT.load(Ordering::SeqCst),
+            "signature": signature.to_string(),
+            "logs": logs,
+        }));
+    }
+}
+
+/// Processes the next transactions at `slot`.
+fn warp_to_slot(test: &mut TestContext, slot: Slot) {
+    test.context.warp_to_slot(slot).unwrap();
+    SLOT.store(slot, Ordering::SeqCst);
+}
+
+#[tokio::test]
+#[ignore]
+async fn record_indexer_fixture() {
+    // the runtime then fails to install its own logger
+    log::set_logger(&RECORDER).unwrap();
+    let mut test = TestContext::new().await;
+    log::set_max_level(log::LevelFilter::Debug);
+
+    take_logs();
+    test.on_processed = Some(Box::new(record_transaction));
+
+    warp_to_slot(&mut test, 90);
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 0,
+            quote_decimals: 0,
+            strike: 2,
+            expiry_ts,
+            is_put: false,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    let alice = test.new_user(&contract).await;
+    let bob = test.new_user(&contract).await;
+    let carol = test.new_user(&contract).await;
+    test.mint_to(&contract.underlying_mint, &alice.underlying_tokens, 10)
+        .await;
+    test.mint_to(&contract.underlying_mint, &carol.underlying_tokens, 5)
+        .await;
+    test.mint_to(&contract.quote_mint, &bob.quote_tokens, 8)
+        .await;
+
+    warp_to_slot(&mut test, 100);
+    test.write(&contract, &alice, 10).await.unwrap();
+
+    warp_to_slot(&mut test, 105);
+    test.write(&contract, &carol, 5).await.unwrap();
+    // the transfer does not involve the program, so it is not recorded
+    test.transfer(&alice.option_tokens, &bob.option_tokens, &alice.keypair, 4)
+        .await
+        .unwrap();
+
+    warp_to_slot(&mut test, 110);
+    test.close(&contract, &carol, 2).await.unwrap();
+
+    warp_to_slot(&mut test, 120);
+    test.exercise(&contract, &bob, 4).await.unwrap();
+
+    warp_to_slot(&mut test, 200);
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    test.warp_to(data.exercise_deadline_ts().unwrap()).await;
+    test.redeem(&contract, &alice, 10).await.unwrap();
+
+    let transactions = std::mem::take(&mut *TRANSACTIONS.lock().unwrap());
+    let fixture = json!({
+        "contract": contract.key.to_string(),
+        "creator": test.payer().pubkey().to_string(),
+        "underlying_mint": contract.underlying_mint.to_string(),
+        "quote_mint": contract.quote_mint.to_string(),
+        "alice": alice.key().to_string(),
+        "bob": bob.key().to_string(),
+        "carol": carol.key().to_string(),
+        "transactions": transactions,
+    });
+    std::fs::write(
+        FIXTURE_PATH,
+        serde_json::to_string_pretty(&fixture).unwrap() + "\n",
+    )
+    .unwrap();
+}
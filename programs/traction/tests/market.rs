@@ -0,0 +1,141 @@
+//! Tests for [traction::traction::new_option_market] and the series appended by
+//! [traction::traction::new_contract].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, system_instruction::SystemError};
+use traction::{ErrorCode, ExerciseSchedule, OptionMarket, OptionSeries};
+
+fn params(strike: u64, expiry_ts: i64, is_put: bool) -> ContractParams {
+    ContractParams {
+        underlying_decimals: 6,
+        quote_decimals: 6,
+        strike,
+        expiry_ts,
+        is_put,
+        exercise_schedule: ExerciseSchedule::default(),
+        exercise_window_secs: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_series_are_enumerable() {
+    let mut test = TestContext::new().await;
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let now = test.now().await;
+
+    let mut contracts = vec![];
+    for (strike, expiry_ts, is_put) in [
+        (1_000_000, now + 1_000, false),
+        (2_000_000, now + 1_000, false),
+        (2_000_000, now + 2_000, true),
+    ] {
+        contracts.push(
+            test.try_new_contract_for_mints(
+                underlying_mint,
+                quote_mint,
+                params(strike, expiry_ts, is_put),
+            )
+            .await
+            .unwrap(),
+        );
+    }
+
+    let (market, _) = find_option_market_address(&underlying_mint, &quote_mint);
+    let market_data: OptionMarket = test.get_anchor_account(market).await;
+    assert_eq!(market_data.underlying_mint, underlying_mint);
+    assert_eq!(market_data.quote_mint, quote_mint);
+    assert_eq!(market_data.num_series, 3);
+
+    for (index, contract) in contracts.iter().enumerate() {
+        let (series, _) = find_option_series_address(&market, index as u64);
+        assert_eq!(series, contract.series);
+        let series_data: OptionSeries = test.get_anchor_account(series).await;
+        assert_eq!(series_data.market, market);
+        assert_eq!(series_data.index, index as u64);
+        assert_eq!(series_data.contract, contract.key);
+    }
+    let last: OptionSeries = test.get_anchor_account(contracts[2].series).await;
+    assert_eq!(last.strike, 2_000_000);
+    assert_eq!(last.expiry_ts, now + 2_000);
+    assert!(last.is_put);
+}
+
+#[tokio::test]
+async fn test_markets_are_per_pair() {
+    let mut test = TestContext::new().await;
+    let now = test.now().await;
+    let first = test
+        .new_contract(params(1_000_000, now + 1_000, false))
+        .await;
+    let second = test
+        .new_contract(params(1_000_000, now + 1_000, false))
+        .await;
+    assert_ne!(first.market, second.market);
+
+    let market_data: OptionMarket = test.get_anchor_account(second.market).await;
+    assert_eq!(market_data.num_series, 1);
+    assert_eq!(
+        second.series,
+        find_option_series_address(&second.market, 0).0
+    );
+}
+
+#[tokio::test]
+async fn test_new_option_market_rejects_matching_mints() {
+    let mut test = TestContext::new().await;
+    let mint = test.create_mint(6).await;
+    assert_error(
+        test.try_new_option_market(mint, mint).await,
+        ErrorCode::UselessMints,
+    );
+}
+
+#[tokio::test]
+async fn test_new_option_market_charges_rent_to_payer() {
+    let mut test = TestContext::new().await;
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let payer = test.payer().pubkey();
+    let (market, _) = find_option_market_address(&underlying_mint, &quote_mint);
+    #[allow(deprecated)]
+    let (fee_calculator, _, _) = test.context.banks_client.get_fees().await.unwrap();
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    let rent_exempt_balance = rent.minimum_balance(account_space::<OptionMarket>());
+
+    let payer_lamports = test.get_account(payer).await.lamports;
+    test.new_option_market(underlying_mint, quote_mint).await;
+    // the harness preallocates the market with a single lamport
+    assert_eq!(
+        payer_lamports - test.get_account(payer).await.lamports,
+        fee_calculator.lamports_per_signature + rent_exempt_balance - 1
+    );
+    assert_eq!(test.get_account(market).await.lamports, rent_exempt_balance);
+}
+
+#[tokio::test]
+async fn test_new_option_market_cannot_be_reinitialized() {
+    let mut test = TestContext::new().await;
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let now = test.now().await;
+    let contract = test
+        .try_new_contract_for_mints(
+            underlying_mint,
+            quote_mint,
+            params(1_000_000, now + 1_000, false),
+        )
+        .await
+        .unwrap();
+
+    assert_system_error(
+        test.try_new_option_market(underlying_mint, quote_mint)
+            .await,
+        SystemError::AccountAlreadyInUse,
+    );
+    let market_data: OptionMarket = test.get_anchor_account(contract.market).await;
+    assert_eq!(market_data.num_series, 1);
+}
@@ -0,0 +1,102 @@
+//! Tests for [traction::traction::new_market_params] and [traction::traction::set_market_params].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use traction::{ErrorCode, ExerciseSchedule, MarketParams};
+
+/// Seconds in a week.
+const WEEK: i64 = 604_800;
+
+/// Offset of Friday at 08:00 UTC from the Unix epoch, a Thursday.
+const FRIDAY_0800: i64 = 115_200;
+
+fn params(strike: u64, expiry_ts: i64) -> ContractParams {
+    ContractParams {
+        underlying_decimals: 6,
+        quote_decimals: 6,
+        strike,
+        expiry_ts,
+        is_put: false,
+        exercise_schedule: ExerciseSchedule::default(),
+        exercise_window_secs: 0,
+    }
+}
+
+/// The first weekly expiry at least a day from now.
+fn next_friday(now: i64) -> i64 {
+    let from = now + 86_400 - FRIDAY_0800;
+    FRIDAY_0800 + (from.div_euclid(WEEK) + 1) * WEEK
+}
+
+#[tokio::test]
+async fn test_market_params_restrict_new_contracts() {
+    let mut test = TestContext::new().await;
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    test.new_market_params(underlying_mint, quote_mint, 1_000_000, WEEK, FRIDAY_0800)
+        .await
+        .unwrap();
+    let (market_params, _) = find_market_params_address(&underlying_mint, &quote_mint);
+    let data: MarketParams = test.get_anchor_account(market_params).await;
+    assert_eq!(data.strike_tick, 1_000_000);
+    assert_eq!(data.expiry_period_secs, WEEK);
+    assert_eq!(data.expiry_offset_secs, FRIDAY_0800);
+
+    let expiry_ts = next_friday(test.now().await);
+    assert_error(
+        test.try_new_contract_for_mints(underlying_mint, quote_mint, params(1_500_000, expiry_ts))
+            .await
+            .map(|_| ()),
+        ErrorCode::StrikeNotOnTick,
+    );
+    assert_error(
+        test.try_new_contract_for_mints(
+            underlying_mint,
+            quote_mint,
+            params(2_000_000, expiry_ts + 1),
+        )
+        .await
+        .map(|_| ()),
+        ErrorCode::ExpiryNotOnGrid,
+    );
+    test.try_new_contract_for_mints(underlying_mint, quote_mint, params(2_000_000, expiry_ts))
+        .await
+        .unwrap();
+
+    // other pairs are unrestricted
+    test.new_contract(params(1_500_000, expiry_ts + 1)).await;
+}
+
+#[tokio::test]
+async fn test_set_market_params() {
+    let mut test = TestContext::new().await;
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    test.new_market_params(underlying_mint, quote_mint, 1_000_000, WEEK, FRIDAY_0800)
+        .await
+        .unwrap();
+
+    // only the admin may update the parameters
+    assert_error(
+        test.set_market_params(underlying_mint, quote_mint, &Keypair::new(), 0, 0, 0)
+            .await,
+        ErrorCode::Unauthorized,
+    );
+    assert_error(
+        test.set_market_params(underlying_mint, quote_mint, &test.payer(), 0, -1, 0)
+            .await,
+        ErrorCode::InvalidExpiryGrid,
+    );
+
+    // lifting the restrictions allows any strike and expiry
+    test.set_market_params(underlying_mint, quote_mint, &test.payer(), 0, 0, 0)
+        .await
+        .unwrap();
+    let expiry_ts = test.now().await + 1_001;
+    test.try_new_contract_for_mints(underlying_mint, quote_mint, params(1_500_001, expiry_ts))
+        .await
+        .unwrap();
+}
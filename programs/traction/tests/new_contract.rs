@@ -0,0 +1,351 @@
+//! Tests for [traction::traction::new_contract].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    system_instruction::SystemError,
+    transport::TransportError,
+};
+use traction::{ErrorCode, ExerciseSchedule, OptionsContract, MAX_CREATOR_FEE_KBPS};
+
+fn params(strike: u64, expiry_ts: i64) -> ContractParams {
+    ContractParams {
+        underlying_decimals: 6,
+        quote_decimals: 6,
+        strike,
+        expiry_ts,
+        is_put: true,
+        exercise_schedule: ExerciseSchedule::default(),
+        exercise_window_secs: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_new_contract() {
+    let mut test = TestContext::new().await;
+    let now = test.now().await;
+    let expiry_ts = now + 1_000;
+    let contract = test.new_contract(params(2_000_000, expiry_ts)).await;
+
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.strike, 2_000_000);
+    assert_eq!(data.strike_units, 1_000_000);
+    assert_eq!(data.expiry_ts, expiry_ts);
+    assert_eq!(data.creator, test.payer().pubkey());
+    assert_eq!(data.payer, test.payer().pubkey());
+    assert_eq!(data.created_at, now);
+    assert_eq!(data.total_written, 0);
+    assert_eq!(data.total_exercised, 0);
+    assert_eq!(data.total_redeemed, 0);
+    assert_eq!(data.total_closed, 0);
+    assert_eq!(data.total_settled, 0);
+}
+
+#[tokio::test]
+async fn test_new_contract_cannot_be_reinitialized() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test.new_contract(params(2_000_000, expiry_ts)).await;
+    test.new_writer(&contract, 1_000_000).await;
+
+    assert_system_error(
+        test.try_new_contract_for_mints(
+            contract.underlying_mint,
+            contract.quote_mint,
+            params(2_000_000, expiry_ts),
+        )
+        .await
+        .map(|_| ()),
+        SystemError::AccountAlreadyInUse,
+    );
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.total_written, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_new_contract_rejects_zero_strike() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    assert_error(
+        test.try_new_contract(params(0, expiry_ts))
+            .await
+            .map(|_| ()),
+        ErrorCode::ZeroStrike,
+    );
+}
+
+#[tokio::test]
+async fn test_new_contract_rejects_past_expiry() {
+    let mut test = TestContext::new().await;
+    let now = test.now().await;
+    for expiry_ts in [now, now - 1] {
+        assert_error(
+            test.try_new_contract(params(2_000_000, expiry_ts))
+                .await
+                .map(|_| ()),
+            ErrorCode::ExpiryInPast,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_new_contract_respects_max_tenor() {
+    let mut test = TestContext::new().await;
+    test.set_protocol_params(traction::DEFAULT_EXERCISE_FEE_KBPS, 1_000)
+        .await
+        .unwrap();
+
+    let now = test.now().await;
+    assert_error(
+        test.try_new_contract(params(2_000_000, now + 1_001))
+            .await
+            .map(|_| ()),
+        ErrorCode::TenorTooLong,
+    );
+    test.new_contract(params(2_000_000, now + 1_000)).await;
+
+    // removing the maximum allows any expiry
+    test.set_protocol_params(traction::DEFAULT_EXERCISE_FEE_KBPS, 0)
+        .await
+        .unwrap();
+    test.new_contract(params(2_000_000, now + 1_000_000_000))
+        .await;
+}
+
+/// Attempts to create a contract over new mints with [ContractOptions].
+async fn try_new_contract_with_options(
+    test: &mut TestContext,
+    options: ContractOptions,
+) -> Result<TestContract, TransportError> {
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let expiry_ts = test.now().await + 1_000;
+    test.try_new_contract_with_options(
+        underlying_mint,
+        quote_mint,
+        params(2_000_000, expiry_ts),
+        options,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_new_contract_validates_mints() {
+    let mut test = TestContext::new().await;
+    for (options, error) in [
+        (
+            ContractOptions {
+                option_decimals: Some(9),
+                ..Default::default()
+            },
+            ErrorCode::OptionDecimalMismatch,
+        ),
+        (
+            ContractOptions {
+                writer_decimals: Some(9),
+                ..Default::default()
+            },
+            ErrorCode::WriterDecimalMismatch,
+        ),
+        (
+            ContractOptions {
+                option_supply: 1,
+                ..Default::default()
+            },
+            ErrorCode::OptionMintMustHaveZeroSupply,
+        ),
+    ] {
+        assert_error(
+            try_new_contract_with_options(&mut test, options)
+                .await
+                .map(|_| ()),
+            error,
+        );
+    }
+
+    // the underlying and quote must differ; no market exists for such a pair,
+    // so the contract is added to that of another pair
+    let expiry_ts = test.now().await + 1_000;
+    let other = test.new_contract(params(2_000_000, expiry_ts)).await;
+    let mint = test.create_mint(6).await;
+    assert_error(
+        test.try_new_contract_with_options(
+            mint,
+            mint,
+            params(2_000_000, expiry_ts),
+            ContractOptions {
+                market: Some(other.market),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ()),
+        ErrorCode::UselessMints,
+    );
+}
+
+#[tokio::test]
+async fn test_new_contract_rejects_market_of_other_pair() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let other = test.new_contract(params(2_000_000, expiry_ts)).await;
+    assert_error(
+        try_new_contract_with_options(
+            &mut test,
+            ContractOptions {
+                market: Some(other.market),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ()),
+        ErrorCode::MarketMismatch,
+    );
+}
+
+#[tokio::test]
+async fn test_creator_fee() {
+    let mut test = TestContext::new().await;
+    let creator_fee_to = Keypair::new().pubkey();
+    assert_error(
+        try_new_contract_with_options(
+            &mut test,
+            ContractOptions {
+                creator_fee_kbps: MAX_CREATOR_FEE_KBPS + 1,
+                creator_fee_to,
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ()),
+        ErrorCode::CreatorFeeTooHigh,
+    );
+
+    // a 0.1% creator fee is charged on the underlying paid to exercise the puts,
+    // alongside the 0.01% exercise fee
+    let contract = try_new_contract_with_options(
+        &mut test,
+        ContractOptions {
+            creator_fee_kbps: 10_000,
+            creator_fee_to,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.creator_fee_kbps, 10_000);
+    assert_eq!(data.creator_fee_to, creator_fee_to);
+
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    test.mint_to(
+        &contract.underlying_mint,
+        &writer.underlying_tokens,
+        1_000_000,
+    )
+    .await;
+    test.exercise(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(contract.creator_fee_tokens).await, 1_000);
+    assert_eq!(
+        test.token_balance(contract.exercise_fee_tokens()).await,
+        100
+    );
+    assert_eq!(
+        test.token_balance(contract.crate_exercise_tokens).await,
+        998_900
+    );
+}
+
+#[tokio::test]
+async fn test_creator_fee_is_part_of_contract_address() {
+    let mut test = TestContext::new().await;
+    assert_error(
+        try_new_contract_with_options(
+            &mut test,
+            ContractOptions {
+                creator_fee_to: Keypair::new().pubkey(),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ()),
+        ErrorCode::CreatorFeeRecipientWithoutFee,
+    );
+
+    // a series with a creator fee does not take the address of the same series without one
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let params = params(2_000_000, test.now().await + 1_000);
+    let with_fee = test
+        .try_new_contract_with_options(
+            underlying_mint,
+            quote_mint,
+            params,
+            ContractOptions {
+                creator_fee_kbps: 10_000,
+                creator_fee_to: Keypair::new().pubkey(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let without_fee = test
+        .try_new_contract_for_mints(underlying_mint, quote_mint, params)
+        .await
+        .unwrap();
+    assert_ne!(with_fee.key, without_fee.key);
+    assert_eq!(
+        without_fee.key,
+        contract_key(
+            &underlying_mint,
+            &quote_mint,
+            &params,
+            &ContractOptions::default()
+        )
+        .find_address()
+        .0
+    );
+    let data: OptionsContract = test.get_anchor_account(without_fee.key).await;
+    assert_eq!(data.creator_fee_kbps, 0);
+}
+
+#[tokio::test]
+async fn test_exercise_window_is_part_of_contract_address() {
+    let mut test = TestContext::new().await;
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let params = params(2_000_000, test.now().await + 1_000);
+
+    // series which only differ by their exercise window do not take each other's address
+    let mut keys = vec![];
+    for exercise_window_secs in [0, 600, u32::MAX] {
+        let params = ContractParams {
+            exercise_window_secs,
+            ..params
+        };
+        let contract = test
+            .try_new_contract_for_mints(underlying_mint, quote_mint, params)
+            .await
+            .unwrap();
+        assert_eq!(
+            contract.key,
+            contract_key(
+                &underlying_mint,
+                &quote_mint,
+                &params,
+                &ContractOptions::default()
+            )
+            .find_address()
+            .0
+        );
+        let data: OptionsContract = test.get_anchor_account(contract.key).await;
+        assert_eq!(data.exercise_window_secs, exercise_window_secs);
+        keys.push(contract.key);
+    }
+    keys.sort();
+    keys.dedup();
+    assert_eq!(keys.len(), 3);
+}
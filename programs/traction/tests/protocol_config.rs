@@ -0,0 +1,200 @@
+//! Tests for [traction::traction::new_protocol_config], [traction::traction::set_protocol_params],
+//! [traction::traction::transfer_admin], [traction::traction::accept_admin],
+//! and [traction::traction::sweep_fees].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    system_instruction::SystemError,
+};
+use traction::{ErrorCode, ExerciseSchedule, ProtocolConfig, MAX_EXERCISE_FEE_KBPS};
+
+#[tokio::test]
+async fn test_new_protocol_config_requires_upgrade_authority() {
+    let mut test = TestContext::start().await;
+    let attacker = Keypair::new();
+    assert_error(
+        test.new_protocol_config(&attacker).await,
+        ErrorCode::Unauthorized,
+    );
+
+    let upgrade_authority = test.payer();
+    test.new_protocol_config(&upgrade_authority).await.unwrap();
+    let data: ProtocolConfig = test
+        .get_anchor_account(find_protocol_config_address().0)
+        .await;
+    assert_eq!(data.admin, upgrade_authority.pubkey());
+    assert_eq!(data.treasury, upgrade_authority.pubkey());
+}
+
+#[tokio::test]
+async fn test_new_protocol_config_cannot_be_reinitialized() {
+    let mut test = TestContext::new().await;
+    test.set_protocol_params(10, 0).await.unwrap();
+
+    let upgrade_authority = test.payer();
+    assert_system_error(
+        test.new_protocol_config(&upgrade_authority).await,
+        SystemError::AccountAlreadyInUse,
+    );
+    let data: ProtocolConfig = test
+        .get_anchor_account(find_protocol_config_address().0)
+        .await;
+    assert_eq!(data.exercise_fee_kbps, 10);
+}
+
+#[tokio::test]
+async fn test_set_protocol_params() {
+    let mut test = TestContext::new().await;
+    test.set_protocol_params(2_000, 86_400).await.unwrap();
+    let data: ProtocolConfig = test
+        .get_anchor_account(find_protocol_config_address().0)
+        .await;
+    assert_eq!(data.exercise_fee_kbps, 2_000);
+    assert_eq!(data.max_tenor_secs, 86_400);
+    assert_eq!(data.treasury, test.payer().pubkey());
+
+    test.set_protocol_params(MAX_EXERCISE_FEE_KBPS, 0)
+        .await
+        .unwrap();
+    assert_error(
+        test.set_protocol_params(MAX_EXERCISE_FEE_KBPS + 1, 0).await,
+        ErrorCode::ExerciseFeeTooHigh,
+    );
+    assert_error(
+        test.set_protocol_params_as(&Keypair::new(), 0, 0).await,
+        ErrorCode::Unauthorized,
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_admin() {
+    let mut test = TestContext::new().await;
+    let admin = test.payer();
+    let new_admin = Keypair::new();
+
+    assert_error(
+        test.accept_admin(&new_admin).await,
+        ErrorCode::NoPendingAdmin,
+    );
+    assert_error(
+        test.transfer_admin(&new_admin, new_admin.pubkey()).await,
+        ErrorCode::Unauthorized,
+    );
+
+    test.transfer_admin(&admin, new_admin.pubkey())
+        .await
+        .unwrap();
+    let data: ProtocolConfig = test
+        .get_anchor_account(find_protocol_config_address().0)
+        .await;
+    assert_eq!(data.admin, admin.pubkey());
+    assert_eq!(data.pending_admin, new_admin.pubkey());
+
+    // only the pending admin may accept
+    assert_error(
+        test.accept_admin(&Keypair::new()).await,
+        ErrorCode::Unauthorized,
+    );
+    test.accept_admin(&new_admin).await.unwrap();
+    let data: ProtocolConfig = test
+        .get_anchor_account(find_protocol_config_address().0)
+        .await;
+    assert_eq!(data.admin, new_admin.pubkey());
+    assert_eq!(data.pending_admin, Default::default());
+
+    assert_error(
+        test.set_protocol_params_as(&admin, 0, 0).await,
+        ErrorCode::Unauthorized,
+    );
+    test.set_protocol_params_as(&new_admin, 0, 0).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sweep_fees() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike: 2_000_000,
+            expiry_ts,
+            is_put: false,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    test.mint_to(&contract.quote_mint, &writer.quote_tokens, 2_000_000)
+        .await;
+    test.exercise(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(
+        test.token_balance(contract.exercise_fee_tokens()).await,
+        200
+    );
+
+    // fees may only be swept to the treasury
+    let other = test.new_user(&contract).await;
+    assert!(test
+        .sweep_fees(&contract.quote_mint, other.quote_tokens)
+        .await
+        .is_err());
+
+    let treasury = test.payer().pubkey();
+    let treasury_tokens = test.create_ata(&treasury, &contract.quote_mint).await;
+    test.sweep_fees(&contract.quote_mint, treasury_tokens)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(treasury_tokens).await, 200);
+    assert_eq!(test.token_balance(contract.exercise_fee_tokens()).await, 0);
+}
+
+#[tokio::test]
+async fn test_sweep_fees_to_new_treasury() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike: 2_000_000,
+            expiry_ts,
+            is_put: false,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    test.mint_to(&contract.quote_mint, &writer.quote_tokens, 2_000_000)
+        .await;
+    test.exercise(&contract, &writer, 1_000_000).await.unwrap();
+
+    // only the admin may move the treasury
+    let admin = test.payer();
+    let dao = Keypair::new();
+    assert_error(
+        test.set_treasury(&dao, dao.pubkey()).await,
+        ErrorCode::Unauthorized,
+    );
+    test.set_treasury(&admin, dao.pubkey()).await.unwrap();
+    let data: ProtocolConfig = test
+        .get_anchor_account(find_protocol_config_address().0)
+        .await;
+    assert_eq!(data.treasury, dao.pubkey());
+
+    // fees are swept to the new treasury, and no longer to the old one
+    let old_treasury_tokens = test.create_ata(&admin.pubkey(), &contract.quote_mint).await;
+    assert!(test
+        .sweep_fees(&contract.quote_mint, old_treasury_tokens)
+        .await
+        .is_err());
+    let dao_tokens = test.create_ata(&dao.pubkey(), &contract.quote_mint).await;
+    test.sweep_fees(&contract.quote_mint, dao_tokens)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(dao_tokens).await, 200);
+}
@@ -0,0 +1,179 @@
+//! Tests for [traction::traction::option_redeem].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use traction::{ErrorCode, ExerciseSchedule, OptionsContract};
+
+/// One option exercises for 2 quote tokens.
+const STRIKE: u64 = 2_000_000;
+
+async fn setup_call(strike: u64) -> (TestContext, TestContract, i64) {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike,
+            expiry_ts,
+            is_put: false,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    (test, contract, expiry_ts)
+}
+
+async fn fund_exercise(test: &mut TestContext, contract: &TestContract, user: &TestUser) {
+    test.mint_to(&contract.quote_mint, &user.quote_tokens, 1_000_000_000_000)
+        .await;
+}
+
+#[tokio::test]
+async fn test_multiple_redeemers_receive_pro_rata_shares() {
+    let (mut test, contract, expiry_ts) = setup_call(STRIKE).await;
+    let alice = test.new_writer(&contract, 1_000_000).await;
+    let bob = test.new_writer(&contract, 2_000_000).await;
+    let carol = test.new_writer(&contract, 3_000_000).await;
+
+    // alice exercises 600_000 options for 1_200_000 quote, less a 120 token fee
+    fund_exercise(&mut test, &contract, &alice).await;
+    test.exercise(&contract, &alice, 600_000).await.unwrap();
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        5_400_000
+    );
+    assert_eq!(
+        test.token_balance(contract.crate_exercise_tokens).await,
+        1_199_880
+    );
+
+    // writers cannot redeem before expiry
+    assert_error(
+        test.redeem(&contract, &bob, 2_000_000).await,
+        ErrorCode::ContractNotYetExpired,
+    );
+
+    test.warp_to(expiry_ts).await;
+
+    let alice_underlying = test.token_balance(alice.underlying_tokens).await;
+    let alice_quote = test.token_balance(alice.quote_tokens).await;
+    test.redeem(&contract, &alice, 1_000_000).await.unwrap();
+    assert_eq!(
+        test.token_balance(alice.underlying_tokens).await - alice_underlying,
+        900_000
+    );
+    assert_eq!(
+        test.token_balance(alice.quote_tokens).await - alice_quote,
+        199_980
+    );
+
+    test.redeem(&contract, &bob, 2_000_000).await.unwrap();
+    assert_eq!(test.token_balance(bob.underlying_tokens).await, 1_800_000);
+    assert_eq!(test.token_balance(bob.quote_tokens).await, 399_960);
+
+    test.redeem(&contract, &carol, 3_000_000).await.unwrap();
+    assert_eq!(test.token_balance(carol.underlying_tokens).await, 2_700_000);
+    assert_eq!(test.token_balance(carol.quote_tokens).await, 599_940);
+
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        0
+    );
+    assert_eq!(test.token_balance(contract.crate_exercise_tokens).await, 0);
+    assert_eq!(test.mint_supply(contract.writer_mint).await, 0);
+
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.total_written, 6_000_000);
+    assert_eq!(data.total_exercised, 600_000);
+    assert_eq!(data.total_redeemed, 6_000_000);
+}
+
+#[tokio::test]
+async fn test_partial_redeem_only_releases_redeemed_share() {
+    let (mut test, contract, expiry_ts) = setup_call(STRIKE).await;
+    let alice = test.new_writer(&contract, 1_000_000).await;
+    let bob = test.new_writer(&contract, 1_000_000).await;
+
+    test.warp_to(expiry_ts).await;
+
+    // redeeming a quarter of the writer tokens only releases a quarter of the collateral
+    test.redeem(&contract, &alice, 250_000).await.unwrap();
+    assert_eq!(test.token_balance(alice.underlying_tokens).await, 250_000);
+    assert_eq!(test.token_balance(alice.writer_tokens).await, 750_000);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        1_750_000
+    );
+
+    // cannot redeem more writer tokens than are held
+    assert!(test.redeem(&contract, &alice, 750_001).await.is_err());
+
+    test.redeem(&contract, &alice, 750_000).await.unwrap();
+    assert_eq!(test.token_balance(alice.underlying_tokens).await, 1_000_000);
+    assert_eq!(test.token_balance(alice.writer_tokens).await, 0);
+
+    // the other writer's share is untouched
+    test.redeem(&contract, &bob, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(bob.underlying_tokens).await, 1_000_000);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_rounding_dust_accrues_to_remaining_writers() {
+    // one option exercises for 1.5 quote tokens
+    let (mut test, contract, expiry_ts) = setup_call(1_500_000).await;
+    let alice = test.new_writer(&contract, 3).await;
+    let bob = test.new_writer(&contract, 3).await;
+    let carol = test.new_writer(&contract, 4).await;
+
+    // 3 options exercise for 4.5 quote tokens, rounded up to 5
+    fund_exercise(&mut test, &contract, &carol).await;
+    test.exercise(&contract, &carol, 3).await.unwrap();
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        7
+    );
+    assert_eq!(test.token_balance(contract.crate_exercise_tokens).await, 5);
+
+    test.warp_to(expiry_ts).await;
+
+    // splitting a redemption never yields more than redeeming at once:
+    // 7 * 1 / 10 = 0, then 7 * 2 / 9 = 1, instead of 7 * 3 / 10 = 2,
+    // and 5 * 1 / 10 = 0, then 5 * 2 / 9 = 1.
+    test.redeem(&contract, &alice, 1).await.unwrap();
+    assert_eq!(test.token_balance(alice.underlying_tokens).await, 0);
+    assert_eq!(test.token_balance(alice.quote_tokens).await, 0);
+    test.redeem(&contract, &alice, 2).await.unwrap();
+    assert_eq!(test.token_balance(alice.underlying_tokens).await, 1);
+    assert_eq!(test.token_balance(alice.quote_tokens).await, 1);
+
+    // 6 * 3 / 7 = 2 and 4 * 3 / 7 = 1
+    test.redeem(&contract, &bob, 3).await.unwrap();
+    assert_eq!(test.token_balance(bob.underlying_tokens).await, 2);
+    assert_eq!(test.token_balance(bob.quote_tokens).await, 1);
+
+    // the last redeemer receives all of the dust
+    let carol_underlying = test.token_balance(carol.underlying_tokens).await;
+    let carol_quote = test.token_balance(carol.quote_tokens).await;
+    test.redeem(&contract, &carol, 4).await.unwrap();
+    assert_eq!(
+        test.token_balance(carol.underlying_tokens).await - carol_underlying,
+        4
+    );
+    assert_eq!(
+        test.token_balance(carol.quote_tokens).await - carol_quote,
+        3
+    );
+
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        0
+    );
+    assert_eq!(test.token_balance(contract.crate_exercise_tokens).await, 0);
+}
@@ -0,0 +1,516 @@
+//! Tests for [traction::traction::new_price_oracle], [traction::traction::set_oracle_price],
+//! [traction::traction::settle_contract], and [traction::traction::option_settle].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, transport::TransportError};
+use traction::{ErrorCode, ExerciseSchedule, ExerciseStyle, OptionsContract, PriceOracle};
+
+const STRIKE: u64 = 2_000_000;
+
+fn params(expiry_ts: i64) -> ContractParams {
+    ContractParams {
+        underlying_decimals: 6,
+        quote_decimals: 6,
+        strike: STRIKE,
+        expiry_ts,
+        is_put: false,
+        exercise_schedule: ExerciseSchedule::default(),
+        exercise_window_secs: 0,
+    }
+}
+
+/// A pair with a [PriceOracle].
+struct OraclePair {
+    underlying_mint: Pubkey,
+    quote_mint: Pubkey,
+    oracle: Pubkey,
+    authority: Keypair,
+}
+
+async fn new_oracle_pair(test: &mut TestContext) -> OraclePair {
+    let underlying_mint = test.create_mint(6).await;
+    let quote_mint = test.create_mint(6).await;
+    let authority = Keypair::new();
+    let oracle = test
+        .new_price_oracle(underlying_mint, quote_mint, &authority)
+        .await;
+    OraclePair {
+        underlying_mint,
+        quote_mint,
+        oracle,
+        authority,
+    }
+}
+
+/// Creates a cash-settled call on a pair with an oracle.
+async fn setup_cash_settled() -> (TestContext, TestContract, OraclePair, i64) {
+    let mut test = TestContext::new().await;
+    let pair = new_oracle_pair(&mut test).await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .try_new_contract_with_options(
+            pair.underlying_mint,
+            pair.quote_mint,
+            params(expiry_ts),
+            ContractOptions {
+                oracle: pair.oracle,
+                is_cash_settled: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    (test, contract, pair, expiry_ts)
+}
+
+#[tokio::test]
+async fn test_set_oracle_price() {
+    let mut test = TestContext::new().await;
+    let pair = new_oracle_pair(&mut test).await;
+
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    let data: PriceOracle = test.get_anchor_account(pair.oracle).await;
+    assert_eq!(data.price, 2_500_000);
+    assert_eq!(data.publish_ts, test.now().await);
+
+    assert_error(
+        test.set_oracle_price(pair.oracle, &Keypair::new(), 1).await,
+        ErrorCode::Unauthorized,
+    );
+}
+
+/// Attempts to create a call on the pair with [ContractOptions].
+async fn try_new_contract(
+    test: &mut TestContext,
+    pair: &OraclePair,
+    expiry_ts: i64,
+    options: ContractOptions,
+) -> Result<(), TransportError> {
+    test.try_new_contract_with_options(
+        pair.underlying_mint,
+        pair.quote_mint,
+        params(expiry_ts),
+        options,
+    )
+    .await
+    .map(|_| ())
+}
+
+#[tokio::test]
+async fn test_new_contract_validates_oracle() {
+    let mut test = TestContext::new().await;
+    let pair = new_oracle_pair(&mut test).await;
+    let other_pair = new_oracle_pair(&mut test).await;
+    let expiry_ts = test.now().await + 1_000;
+
+    assert_error(
+        try_new_contract(
+            &mut test,
+            &pair,
+            expiry_ts,
+            ContractOptions {
+                is_cash_settled: true,
+                ..Default::default()
+            },
+        )
+        .await,
+        ErrorCode::NoOracle,
+    );
+    assert_error(
+        try_new_contract(
+            &mut test,
+            &pair,
+            expiry_ts,
+            ContractOptions {
+                oracle: other_pair.oracle,
+                ..Default::default()
+            },
+        )
+        .await,
+        ErrorCode::OracleMintMismatch,
+    );
+    assert_error(
+        try_new_contract(
+            &mut test,
+            &pair,
+            expiry_ts,
+            ContractOptions {
+                oracle: pair.underlying_mint,
+                ..Default::default()
+            },
+        )
+        .await,
+        ErrorCode::UnsupportedOracle,
+    );
+    // cash-settled options are only settled at expiry
+    assert_error(
+        test.try_new_contract_with_options(
+            pair.underlying_mint,
+            pair.quote_mint,
+            ContractParams {
+                exercise_schedule: ExerciseSchedule {
+                    style: ExerciseStyle::European,
+                    window_secs: 100,
+                    period_secs: 0,
+                },
+                ..params(expiry_ts)
+            },
+            ContractOptions {
+                oracle: pair.oracle,
+                is_cash_settled: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ()),
+        ErrorCode::InvalidExerciseSchedule,
+    );
+
+    try_new_contract(
+        &mut test,
+        &pair,
+        expiry_ts,
+        ContractOptions {
+            oracle: pair.oracle,
+            is_cash_settled: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_settle_contract_without_oracle_fails() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test.new_contract(params(expiry_ts)).await;
+    test.warp_to(expiry_ts).await;
+    assert_error(test.settle_contract(&contract).await, ErrorCode::NoOracle);
+}
+
+#[tokio::test]
+async fn test_cash_settled_call() {
+    let (mut test, contract, pair, expiry_ts) = setup_cash_settled().await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    let holder = test.new_user(&contract).await;
+    test.transfer(
+        &writer.option_tokens,
+        &holder.option_tokens,
+        &writer.keypair,
+        1_000_000,
+    )
+    .await
+    .unwrap();
+    test.mint_to(&contract.quote_mint, &holder.quote_tokens, 2_000_000)
+        .await;
+
+    assert_error(
+        test.exercise(&contract, &holder, 1_000_000).await,
+        ErrorCode::CashSettledContract,
+    );
+    assert_error(
+        test.settle(&contract, &holder, &holder.keypair, 1_000_000)
+            .await,
+        ErrorCode::ContractNotSettled,
+    );
+    assert_error(
+        test.settle_contract(&contract).await,
+        ErrorCode::ContractNotYetExpired,
+    );
+
+    // a price published before expiry cannot settle the contract
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.settle_contract(&contract).await,
+        ErrorCode::StaleSettlementPrice,
+    );
+    assert_error(
+        test.redeem(&contract, &writer, 1_000_000).await,
+        ErrorCode::ContractNotSettled,
+    );
+
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    test.settle_contract(&contract).await.unwrap();
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert!(data.is_settled);
+    assert_eq!(data.settlement_price, 2_500_000);
+    assert_error(
+        test.settle_contract(&contract).await,
+        ErrorCode::ContractAlreadySettled,
+    );
+
+    // writers can only redeem the collateral not owed to holders,
+    // and the contract cannot be closed until holders have settled
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 800_000);
+    assert_error(
+        test.close_contract(&contract).await,
+        ErrorCode::SettlementOutstanding,
+    );
+
+    // each option is worth 0.5 quote tokens, or 0.2 underlying tokens at the settlement price
+    assert_error(
+        test.settle(&contract, &holder, &writer.keypair, 1_000_000)
+            .await,
+        ErrorCode::Unauthorized,
+    );
+    test.settle(&contract, &holder, &holder.keypair, 1_000_000)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(holder.underlying_tokens).await, 200_000);
+    assert_eq!(test.token_balance(holder.option_tokens).await, 0);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        0
+    );
+
+    test.close_contract(&contract).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_close_unsettled_contract_fails() {
+    let (mut test, contract, pair, expiry_ts) = setup_cash_settled().await;
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.close_contract(&contract).await,
+        ErrorCode::ContractNotSettled,
+    );
+
+    // out of the money contracts can be closed once settled
+    test.set_oracle_price(pair.oracle, &pair.authority, 1_000_000)
+        .await
+        .unwrap();
+    test.settle_contract(&contract).await.unwrap();
+    test.close_contract(&contract).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_late_settlement_pays_holders() {
+    let (mut test, contract, pair, expiry_ts) = setup_cash_settled().await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+
+    // nobody records the settlement price until long after the oracle has moved on
+    test.warp_to(expiry_ts + 30 * 24 * 60 * 60).await;
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    test.settle_contract(&contract).await.unwrap();
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.settlement_price, 2_500_000);
+
+    // the options are still worth (2.5 - 2) / 2.5 underlying tokens each
+    test.settle(&contract, &writer, &writer.keypair, 1_000_000)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 200_000);
+}
+
+#[tokio::test]
+async fn test_settle_contract_waits_for_oracle_price() {
+    let (mut test, contract, pair, expiry_ts) = setup_cash_settled().await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+
+    // a price published before expiry does not reflect the market at expiry
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    test.warp_to(expiry_ts + 30 * 24 * 60 * 60).await;
+    assert_error(
+        test.settle_contract(&contract).await,
+        ErrorCode::StaleSettlementPrice,
+    );
+
+    // the options are never settled without a price, so writers must wait for one
+    assert_error(
+        test.redeem(&contract, &writer, 1_000_000).await,
+        ErrorCode::ContractNotSettled,
+    );
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert!(!data.is_settled);
+}
+
+/// Creates a physically settled option with an oracle, which is exercised automatically.
+async fn setup_physical(
+    is_put: bool,
+    exercise_schedule: ExerciseSchedule,
+) -> (TestContext, TestContract, OraclePair, TestUser, TestUser) {
+    let mut test = TestContext::new().await;
+    let pair = new_oracle_pair(&mut test).await;
+    let expiry_ts = test.now().await + 10_000;
+    let contract = test
+        .try_new_contract_with_options(
+            pair.underlying_mint,
+            pair.quote_mint,
+            ContractParams {
+                is_put,
+                exercise_schedule,
+                exercise_window_secs: 100,
+                ..params(expiry_ts)
+            },
+            ContractOptions {
+                oracle: pair.oracle,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    let holder = test.new_user(&contract).await;
+    test.transfer(
+        &writer.option_tokens,
+        &holder.option_tokens,
+        &writer.keypair,
+        1_000_000,
+    )
+    .await
+    .unwrap();
+    (test, contract, pair, writer, holder)
+}
+
+#[tokio::test]
+async fn test_auto_exercise_european_call() {
+    let (mut test, contract, pair, writer, holder) = setup_physical(
+        false,
+        ExerciseSchedule {
+            style: ExerciseStyle::European,
+            window_secs: 100,
+            period_secs: 0,
+        },
+    )
+    .await;
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    let deadline_ts = data.exercise_deadline_ts().unwrap();
+
+    // options are only exercised automatically once the exercise window has closed
+    test.warp_to(data.expiry_ts).await;
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    assert_error(
+        test.settle_contract(&contract).await,
+        ErrorCode::ContractNotYetExpired,
+    );
+
+    test.warp_to(deadline_ts).await;
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    test.settle_contract(&contract).await.unwrap();
+
+    // each option is worth 0.5 quote tokens, or 0.2 underlying tokens at the settlement price
+    test.settle(&contract, &holder, &holder.keypair, 1_000_000)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(holder.underlying_tokens).await, 200_000);
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.total_settled, 1_000_000);
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 800_000);
+    test.close_contract(&contract).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_auto_exercise_bermudan_put() {
+    let (mut test, contract, pair, writer, holder) = setup_physical(
+        true,
+        ExerciseSchedule {
+            style: ExerciseStyle::Bermudan,
+            window_secs: 100,
+            period_secs: 1_000,
+        },
+    )
+    .await;
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    test.warp_to(data.exercise_deadline_ts().unwrap()).await;
+    test.set_oracle_price(pair.oracle, &pair.authority, 1_500_000)
+        .await
+        .unwrap();
+    test.settle_contract(&contract).await.unwrap();
+
+    // writers cannot redeem the collateral owed to holders of in-the-money puts
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.quote_tokens).await, 1_500_000);
+    assert_error(
+        test.close_contract(&contract).await,
+        ErrorCode::SettlementOutstanding,
+    );
+
+    // each put is worth the strike less the settlement price, paid in quote tokens
+    test.settle(&contract, &holder, &holder.keypair, 1_000_000)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(holder.quote_tokens).await, 500_000);
+    test.close_contract(&contract).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_late_auto_exercise_pays_holders() {
+    let (mut test, contract, pair, writer, holder) =
+        setup_physical(false, ExerciseSchedule::default()).await;
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+
+    // nobody records the settlement price until weeks after the exercise window closed
+    test.warp_to(data.exercise_deadline_ts().unwrap() + 30 * 24 * 60 * 60)
+        .await;
+    assert_error(
+        test.settle_contract(&contract).await,
+        ErrorCode::StaleSettlementPrice,
+    );
+    assert_error(
+        test.redeem(&contract, &writer, 1_000_000).await,
+        ErrorCode::ContractNotSettled,
+    );
+    test.set_oracle_price(pair.oracle, &pair.authority, 2_500_000)
+        .await
+        .unwrap();
+    test.settle_contract(&contract).await.unwrap();
+
+    // the holder still receives the intrinsic value of the unexercised calls
+    test.settle(&contract, &holder, &holder.keypair, 1_000_000)
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(holder.underlying_tokens).await, 200_000);
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 800_000);
+}
+
+#[tokio::test]
+async fn test_physical_contract_without_oracle_is_not_auto_exercised() {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test.new_contract(params(expiry_ts)).await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    let holder = test.new_user(&contract).await;
+    test.transfer(
+        &writer.option_tokens,
+        &holder.option_tokens,
+        &writer.keypair,
+        1_000_000,
+    )
+    .await
+    .unwrap();
+
+    // unexercised options are worthless after the deadline, and writers keep the collateral
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.settle(&contract, &holder, &holder.keypair, 1_000_000)
+            .await,
+        ErrorCode::ContractNotSettled,
+    );
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(
+        test.token_balance(writer.underlying_tokens).await,
+        1_000_000
+    );
+}
@@ -0,0 +1,192 @@
+//! Tests for [traction::traction::option_write].
+
+mod common;
+
+use common::*;
+use solana_program_test::tokio;
+use traction::{ErrorCode, ExerciseSchedule, OptionsContract};
+
+/// One underlying token is exercised for 2 quote tokens.
+const STRIKE: u64 = 2_000_000;
+
+async fn setup(is_put: bool) -> (TestContext, TestContract, i64) {
+    let mut test = TestContext::new().await;
+    let expiry_ts = test.now().await + 1_000;
+    let contract = test
+        .new_contract(ContractParams {
+            underlying_decimals: 6,
+            quote_decimals: 6,
+            strike: STRIKE,
+            expiry_ts,
+            is_put,
+            exercise_schedule: ExerciseSchedule::default(),
+            exercise_window_secs: 0,
+        })
+        .await;
+    (test, contract, expiry_ts)
+}
+
+#[tokio::test]
+async fn test_write_call() {
+    let (mut test, contract, _) = setup(false).await;
+    let writer = test.new_user(&contract).await;
+    test.mint_to(
+        &contract.underlying_mint,
+        &writer.underlying_tokens,
+        1_500_000,
+    )
+    .await;
+
+    test.write(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.underlying_tokens).await, 500_000);
+    assert_eq!(test.token_balance(writer.option_tokens).await, 1_000_000);
+    assert_eq!(test.token_balance(writer.writer_tokens).await, 1_000_000);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        1_000_000
+    );
+    assert_eq!(test.mint_supply(contract.option_mint).await, 1_000_000);
+    assert_eq!(test.mint_supply(contract.writer_mint).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_write_put() {
+    let (mut test, contract, _) = setup(true).await;
+    let writer = test.new_user(&contract).await;
+    test.mint_to(&contract.quote_mint, &writer.quote_tokens, 2_000_000)
+        .await;
+
+    // each put is backed by the strike in quote tokens
+    test.write(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.quote_tokens).await, 0);
+    assert_eq!(test.token_balance(writer.option_tokens).await, 1_000_000);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        2_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_write_insufficient_collateral_fails() {
+    for is_put in [false, true] {
+        let (mut test, contract, _) = setup(is_put).await;
+        let writer = test.new_user(&contract).await;
+        let collateral_tokens = contract.collateral_tokens(&writer);
+        test.mint_to(&contract.collateral_mint, &collateral_tokens, 999_999)
+            .await;
+
+        let write_amount = if is_put { 500_000 } else { 1_000_000 };
+        assert_error(
+            test.write(&contract, &writer, write_amount).await,
+            ErrorCode::InsufficientCollateral,
+        );
+        test.write(&contract, &writer, write_amount - 1)
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_write_after_expiry_fails() {
+    let (mut test, contract, expiry_ts) = setup(false).await;
+    let writer = test.new_user(&contract).await;
+    test.mint_to(&contract.underlying_mint, &writer.underlying_tokens, 2)
+        .await;
+
+    test.warp_to(expiry_ts - 1).await;
+    test.write(&contract, &writer, 1).await.unwrap();
+    test.warp_to(expiry_ts).await;
+    assert_error(
+        test.write(&contract, &writer, 1).await,
+        ErrorCode::ContractExpired,
+    );
+}
+
+#[tokio::test]
+async fn test_multiple_writers_share_exercise_proceeds() {
+    let (mut test, contract, expiry_ts) = setup(true).await;
+    let alice = test.new_writer(&contract, 1_000_000).await;
+    let bob = test.new_writer(&contract, 3_000_000).await;
+    assert_eq!(test.mint_supply(contract.option_mint).await, 4_000_000);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        8_000_000
+    );
+
+    // a holder exercises half of the puts, selling 2_000_000 underlying for 4_000_000 quote,
+    // of which 200 underlying is the exercise fee
+    let holder = test.new_user(&contract).await;
+    test.transfer(
+        &bob.option_tokens,
+        &holder.option_tokens,
+        &bob.keypair,
+        2_000_000,
+    )
+    .await
+    .unwrap();
+    test.mint_to(
+        &contract.underlying_mint,
+        &holder.underlying_tokens,
+        2_000_000,
+    )
+    .await;
+    test.exercise(&contract, &holder, 2_000_000).await.unwrap();
+    assert_eq!(test.token_balance(holder.quote_tokens).await, 4_000_000);
+
+    let data: OptionsContract = test.get_anchor_account(contract.key).await;
+    assert_eq!(data.total_written, 4_000_000);
+    assert_eq!(data.total_exercised, 2_000_000);
+
+    // writers share the remaining collateral and the exercise proceeds pro rata
+    test.warp_to(expiry_ts).await;
+    test.redeem(&contract, &alice, 1_000_000).await.unwrap();
+    test.redeem(&contract, &bob, 3_000_000).await.unwrap();
+    assert_eq!(test.token_balance(alice.quote_tokens).await, 1_000_000);
+    assert_eq!(test.token_balance(alice.underlying_tokens).await, 499_950);
+    assert_eq!(test.token_balance(bob.quote_tokens).await, 3_000_000);
+    assert_eq!(test.token_balance(bob.underlying_tokens).await, 1_499_850);
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        0
+    );
+    assert_eq!(test.token_balance(contract.crate_exercise_tokens).await, 0);
+}
+
+#[tokio::test]
+async fn test_undercollateralized_crate_halts_instructions() {
+    let (mut test, contract, expiry_ts) = setup(true).await;
+    let writer = test.new_writer(&contract, 1_000_000).await;
+    assert_eq!(
+        test.token_balance(contract.crate_collateral_tokens).await,
+        2_000_000
+    );
+
+    // 500_000 options appear without collateral
+    test.set_mint_supply(&contract.option_mint, 1_500_000).await;
+
+    test.mint_to(&contract.quote_mint, &writer.quote_tokens, 2_000)
+        .await;
+    assert_error(
+        test.write(&contract, &writer, 1_000).await,
+        ErrorCode::Undercollateralized,
+    );
+    test.mint_to(
+        &contract.underlying_mint,
+        &writer.underlying_tokens,
+        100_000,
+    )
+    .await;
+    assert_error(
+        test.exercise(&contract, &writer, 100_000).await,
+        ErrorCode::Undercollateralized,
+    );
+    assert_error(
+        test.close(&contract, &writer, 100_000).await,
+        ErrorCode::Undercollateralized,
+    );
+
+    // expired options no longer need collateral
+    test.warp_to(expiry_ts).await;
+    test.redeem(&contract, &writer, 1_000_000).await.unwrap();
+    assert_eq!(test.token_balance(writer.quote_tokens).await, 2_002_000);
+}
@@ -19,7 +19,16 @@ export const FEE_OWNER = new PublicKey(
 );
 
 /**
- * Exercise fee. (1bp)
+ * The upgradeable BPF loader, which owns the program data of the Traction program.
+ */
+export const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+/**
+ * Default exercise fee. (1bp)
+ *
+ * The actual fee is stored on the protocol config and may be changed by the DAO.
  */
 export const EXERCISE_FEE = new Percent(1, 10_000);
 
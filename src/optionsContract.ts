@@ -10,11 +10,17 @@ import {
   TokenAmount,
 } from "@saberhq/token-utils";
 import type { PublicKey } from "@solana/web3.js";
+import { SystemProgram } from "@solana/web3.js";
 
 import { FEE_OWNER } from "./constants";
-import { findOptionsContractAddress } from "./pda";
-import type { OptionsContractData, TractionProgram } from "./programs/traction";
+import { findOptionsContractAddress, findProtocolConfigAddress } from "./pda";
+import type {
+  ExerciseSchedule,
+  OptionsContractData,
+  TractionProgram,
+} from "./programs/traction";
 import type { TractionSDK } from "./traction";
+import { AMERICAN_EXERCISE_SCHEDULE } from "./utils";
 
 /**
  * Wrapper for interacting with an options contract.
@@ -26,7 +32,13 @@ export class OptionsContract {
     readonly sdk: TractionSDK,
     readonly strike: Price,
     readonly expiryTs: number,
-    readonly isPut: boolean
+    readonly isPut: boolean,
+    readonly exerciseSchedule: ExerciseSchedule = AMERICAN_EXERCISE_SCHEDULE,
+    readonly exerciseWindowSecs: number = 0,
+    readonly isCashSettled: boolean = false,
+    readonly oracle: PublicKey = SystemProgram.programId,
+    readonly creatorFeeKBPS: number = 0,
+    readonly creatorFeeTo: PublicKey = SystemProgram.programId
   ) {}
 
   get program(): TractionProgram {
@@ -52,11 +64,11 @@ export class OptionsContract {
   }
 
   /**
-   * Amount of strike tokens for `10**9` of the underlying.
+   * Amount of strike tokens for one whole unit (`10**decimals`) of the underlying.
    * This is used to compute the PDA.
    */
   get rawStrike(): u64 {
-    return this.strike.quote(new TokenAmount(this.underlying, 10 ** 9)).toU64();
+    return this.strikeQuoteForUnderlying.toU64();
   }
 
   /**
@@ -214,11 +226,21 @@ export class OptionsContract {
       strike: this.rawStrike,
       expiryTs: this.expiryTs,
       isPut: this.isPut,
+      exerciseSchedule: this.exerciseSchedule,
+      exerciseWindowSecs: this.exerciseWindowSecs,
+      isCashSettled: this.isCashSettled,
+      oracle: this.oracle,
+      creatorFeeKBPS: this.creatorFeeKBPS,
+      creatorFeeTo: this.creatorFeeTo,
     });
   }
 
   /**
-   * Writes an option
+   * Writes options.
+   *
+   * `writeAmount` is the number of options to write, which have the decimals of the underlying.
+   * Calls pull `writeAmount` of the underlying as collateral; puts pull the strike
+   * of `writeAmount` in the quote.
    * @returns
    */
   async write({
@@ -304,6 +326,14 @@ export class OptionsContract {
       mint: this.exerciseToken.mintAccount,
       owner: FEE_OWNER,
     });
+    const [protocolConfig] = await findProtocolConfigAddress();
+    // the creator fee destination is not used if there is no creator fee
+    const creatorFeeDestination = contractData.creatorFeeKbps.isZero()
+      ? exerciseFeeDestination
+      : await getATAAddress({
+          mint: this.exerciseToken.mintAccount,
+          owner: contractData.creatorFeeTo,
+        });
 
     const exerciseIX = this.program.instruction.optionExercise(
       optionAmount.toU64(),
@@ -320,6 +350,8 @@ export class OptionsContract {
           crateExerciseTokens: crateATAs.accounts.exercise,
           collateralTokenDestination: writerATAs.accounts.collateral,
           exerciseFeeDestination,
+          protocolConfig,
+          creatorFeeDestination,
 
           tokenProgram: TOKEN_PROGRAM_ID,
           crateTokenProgram: CRATE_ADDRESSES.CrateToken,
@@ -334,6 +366,88 @@ export class OptionsContract {
     ]);
   }
 
+  /**
+   * Closes a position by burning options and writer tokens for their collateral.
+   * @returns
+   */
+  async close({
+    closerAuthority = this.provider.wallet.publicKey,
+    closeAmount,
+  }: {
+    closerAuthority?: PublicKey;
+    closeAmount: TokenAmount;
+  }): Promise<TransactionEnvelope> {
+    const { key: contract, data: contractData } = await this.fetch();
+
+    const closerATAs = await getOrCreateATAs({
+      provider: this.provider,
+      mints: {
+        collateral: this.collateralToken.mintAccount,
+        option: contractData.optionMint,
+        writer: contractData.writerMint,
+      },
+    });
+
+    const closeIX = this.program.instruction.optionClose(closeAmount.toU64(), {
+      accounts: {
+        closerAuthority,
+        contract,
+
+        optionMint: contractData.optionMint,
+        optionTokenSource: closerATAs.accounts.option,
+        writerMint: contractData.writerMint,
+        writerTokenSource: closerATAs.accounts.writer,
+        writerCrateToken: contractData.writerCrate,
+        crateCollateralTokens: contractData.crateCollateralTokens,
+        collateralTokenDestination: closerATAs.accounts.collateral,
+
+        tokenProgram: TOKEN_PROGRAM_ID,
+        crateTokenProgram: CRATE_ADDRESSES.CrateToken,
+      },
+    });
+
+    return new TransactionEnvelope(this.provider, [
+      ...closerATAs.instructions,
+      closeIX,
+    ]);
+  }
+
+  /**
+   * Closes the expired, fully redeemed contract and its series, refunding their rent to its payer.
+   * Tokens left in the writer crate are sent to the payer, whose token accounts are created if needed.
+   * @returns
+   */
+  async closeContract(): Promise<TransactionEnvelope> {
+    const { key: contract, data: contractData } = await this.fetch();
+    const payerATAs = await getOrCreateATAs({
+      provider: this.provider,
+      mints: {
+        collateral: this.collateralToken.mintAccount,
+        exercise: this.exerciseToken.mintAccount,
+      },
+      owner: contractData.payer,
+    });
+    return new TransactionEnvelope(this.provider, [
+      ...payerATAs.instructions,
+      this.program.instruction.closeContract({
+        accounts: {
+          contract,
+          series: contractData.series,
+          writerMint: contractData.writerMint,
+          optionMint: contractData.optionMint,
+          writerCrateToken: contractData.writerCrate,
+          crateCollateralTokens: contractData.crateCollateralTokens,
+          crateExerciseTokens: contractData.crateExerciseTokens,
+          collateralDestination: payerATAs.accounts.collateral,
+          exerciseDestination: payerATAs.accounts.exercise,
+          payer: contractData.payer,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          crateTokenProgram: CRATE_ADDRESSES.CrateToken,
+        },
+      }),
+    ]);
+  }
+
   /**
    * Fetches the data associated with the contract.
    */
@@ -408,6 +522,7 @@ export class OptionsContract {
 
           writerTokenSource: writerATAs.accounts.writer,
           writerMint: contractData.writerMint,
+          optionMint: contractData.optionMint,
           crateCollateralTokens: crateATAs.accounts.collateral,
           crateExerciseTokens: crateATAs.accounts.exercise,
           underlyingTokenDestination: writerATAs.accounts.underlying,
@@ -1,8 +1,13 @@
 import { utils } from "@project-serum/anchor";
 import { u64 } from "@saberhq/token-utils";
-import { PublicKey } from "@solana/web3.js";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
 
-import { TRACTION_ADDRESSES } from "./constants";
+import {
+  BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+  TRACTION_ADDRESSES,
+} from "./constants";
+import type { ExerciseSchedule } from "./programs/traction";
+import { encodeExerciseSchedule } from "./utils";
 
 /**
  * Finds the address of the options contract.
@@ -15,6 +20,12 @@ export const findOptionsContractAddress = async ({
   strike,
   expiryTs,
   isPut,
+  exerciseSchedule,
+  exerciseWindowSecs = 0,
+  isCashSettled = false,
+  oracle = SystemProgram.programId,
+  creatorFeeKBPS = 0,
+  creatorFeeTo = SystemProgram.programId,
 }: {
   programId?: PublicKey;
   underlyingMint: PublicKey;
@@ -22,7 +33,27 @@ export const findOptionsContractAddress = async ({
   strike: u64;
   expiryTs: number;
   isPut: boolean;
+  exerciseSchedule: ExerciseSchedule;
+  /**
+   * Number of seconds after expiry that options may still be exercised.
+   */
+  exerciseWindowSecs?: number;
+  isCashSettled?: boolean;
+  /**
+   * Oracle of a cash-settled contract. Physically settled contracts use the system program.
+   */
+  oracle?: PublicKey;
+  /**
+   * Creator fee charged on exercise, in thousands of BPS.
+   */
+  creatorFeeKBPS?: number;
+  /**
+   * Owner of the accounts that receive the creator fee. Contracts without a creator fee use the system program.
+   */
+  creatorFeeTo?: PublicKey;
 }): Promise<[PublicKey, number]> => {
+  const exerciseWindowSeed = Buffer.alloc(4);
+  exerciseWindowSeed.writeUInt32LE(exerciseWindowSecs);
   return await PublicKey.findProgramAddress(
     [
       utils.bytes.utf8.encode("OptionsContract"),
@@ -31,6 +62,111 @@ export const findOptionsContractAddress = async ({
       strike.toBuffer(),
       new u64(expiryTs).toBuffer(),
       Buffer.from([isPut ? 1 : 0]),
+      encodeExerciseSchedule(exerciseSchedule),
+      exerciseWindowSeed,
+      Buffer.from([isCashSettled ? 1 : 0]),
+      oracle.toBuffer(),
+      new u64(creatorFeeKBPS).toBuffer(),
+      creatorFeeTo.toBuffer(),
+    ],
+    programId
+  );
+};
+
+/**
+ * Finds the address of the protocol config.
+ * @returns
+ */
+export const findProtocolConfigAddress = async ({
+  programId = TRACTION_ADDRESSES.Traction,
+}: {
+  programId?: PublicKey;
+} = {}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("ProtocolConfig")],
+    programId
+  );
+};
+
+/**
+ * Finds the address of the program data of the upgradeable Traction program.
+ * @returns
+ */
+export const findProgramDataAddress = async ({
+  programId = TRACTION_ADDRESSES.Traction,
+}: {
+  programId?: PublicKey;
+} = {}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+  );
+};
+
+/**
+ * Finds the address of the option market of an underlying/quote pair.
+ * @returns
+ */
+export const findOptionMarketAddress = async ({
+  programId = TRACTION_ADDRESSES.Traction,
+  underlyingMint,
+  quoteMint,
+}: {
+  programId?: PublicKey;
+  underlyingMint: PublicKey;
+  quoteMint: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("OptionMarket"),
+      underlyingMint.toBuffer(),
+      quoteMint.toBuffer(),
+    ],
+    programId
+  );
+};
+
+/**
+ * Finds the address of the series at `index` of an option market.
+ * @returns
+ */
+export const findOptionSeriesAddress = async ({
+  programId = TRACTION_ADDRESSES.Traction,
+  market,
+  index,
+}: {
+  programId?: PublicKey;
+  market: PublicKey;
+  index: number;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("OptionSeries"),
+      market.toBuffer(),
+      new u64(index).toBuffer(),
+    ],
+    programId
+  );
+};
+
+/**
+ * Finds the address of the market params of an underlying/quote pair.
+ * @returns
+ */
+export const findMarketParamsAddress = async ({
+  programId = TRACTION_ADDRESSES.Traction,
+  underlyingMint,
+  quoteMint,
+}: {
+  programId?: PublicKey;
+  underlyingMint: PublicKey;
+  quoteMint: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("MarketParams"),
+      underlyingMint.toBuffer(),
+      quoteMint.toBuffer(),
     ],
     programId
   );
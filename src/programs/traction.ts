@@ -6,6 +6,9 @@ export type TractionTypes = AnchorTypes<
   TractionIDL,
   {
     optionsContract: OptionsContractData;
+    optionMarket: OptionMarketData;
+    optionSeries: OptionSeriesData;
+    marketParams: MarketParamsData;
   }
 >;
 
@@ -16,6 +19,30 @@ type Accounts = TractionTypes["Accounts"];
  */
 export type OptionsContractData = Accounts["OptionsContract"];
 
+/**
+ * Registry of the contracts of an underlying/quote pair.
+ */
+export type OptionMarketData = Accounts["OptionMarket"];
+
+/**
+ * An entry of an option market.
+ */
+export type OptionSeriesData = Accounts["OptionSeries"];
+
+/**
+ * Restrictions on the strikes and expiries of new contracts of a pair.
+ */
+export type MarketParamsData = Accounts["MarketParams"];
+
+/**
+ * Defines when an option may be exercised.
+ */
+export type ExerciseSchedule = {
+  style: { american: unknown } | { european: unknown } | { bermudan: unknown };
+  windowSecs: number;
+  periodSecs: number;
+};
+
 export type TractionError = TractionTypes["Error"];
 export type TractionProgram = TractionTypes["Program"];
 
@@ -23,3 +50,6 @@ export type OptionWriteEvent = TractionTypes["Events"]["OptionWriteEvent"];
 export type OptionRedeemEvent = TractionTypes["Events"]["OptionRedeemEvent"];
 export type OptionExerciseEvent =
   TractionTypes["Events"]["OptionExerciseEvent"];
+export type OptionCloseEvent = TractionTypes["Events"]["OptionCloseEvent"];
+export type NewContractEvent = TractionTypes["Events"]["NewContractEvent"];
+export type OptionSettleEvent = TractionTypes["Events"]["OptionSettleEvent"];
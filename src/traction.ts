@@ -22,7 +22,19 @@ import { Keypair, SystemProgram } from "@solana/web3.js";
 import { FEE_OWNER, TRACTION_ADDRESSES } from "./constants";
 import { TractionJSON } from "./idls/traction";
 import { OptionsContract } from "./optionsContract";
-import type { TractionProgram } from "./programs/traction";
+import {
+  findMarketParamsAddress,
+  findOptionMarketAddress,
+  findOptionSeriesAddress,
+  findProgramDataAddress,
+  findProtocolConfigAddress,
+} from "./pda";
+import type {
+  ExerciseSchedule,
+  OptionSeriesData,
+  TractionProgram,
+} from "./programs/traction";
+import { AMERICAN_EXERCISE_SCHEDULE } from "./utils";
 
 /**
  * Programs associated with the Traction protocol.
@@ -63,10 +75,109 @@ export class TractionSDK {
     return TractionSDK.init(this.provider.withSigner(signer));
   }
 
+  /**
+   * Creates the protocol config. This only needs to be called once per deployment,
+   * and the admin must be the upgrade authority of the program.
+   * @returns
+   */
+  async newProtocolConfig({
+    admin = this.provider.wallet.publicKey,
+    payer = this.provider.wallet.publicKey,
+    treasury,
+  }: {
+    admin?: PublicKey;
+    payer?: PublicKey;
+    treasury: PublicKey;
+  }): Promise<TransactionEnvelope> {
+    const [protocolConfig, bump] = await findProtocolConfigAddress();
+    const [programData] = await findProgramDataAddress();
+    return new TransactionEnvelope(this.provider, [
+      this.programs.Traction.instruction.newProtocolConfig(bump, treasury, {
+        accounts: {
+          protocolConfig,
+          admin,
+          programData,
+          payer,
+          systemProgram: SystemProgram.programId,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Creates the option market of an underlying/quote pair.
+   * @returns
+   */
+  async newOptionMarket({
+    underlyingMint,
+    quoteMint,
+    payer = this.provider.wallet.publicKey,
+  }: {
+    underlyingMint: PublicKey;
+    quoteMint: PublicKey;
+    payer?: PublicKey;
+  }): Promise<TransactionEnvelope> {
+    const [market, bump] = await findOptionMarketAddress({
+      underlyingMint,
+      quoteMint,
+    });
+    return new TransactionEnvelope(this.provider, [
+      this.programs.Traction.instruction.newOptionMarket(bump, {
+        accounts: {
+          market,
+          underlyingMint,
+          quoteMint,
+          payer,
+          systemProgram: SystemProgram.programId,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Fetches every series of the option market of an underlying/quote pair, skipping those of closed contracts.
+   * @returns
+   */
+  async fetchOptionSeries({
+    underlyingMint,
+    quoteMint,
+  }: {
+    underlyingMint: PublicKey;
+    quoteMint: PublicKey;
+  }): Promise<{ key: PublicKey; data: OptionSeriesData }[]> {
+    const [market] = await findOptionMarketAddress({
+      underlyingMint,
+      quoteMint,
+    });
+    const marketData =
+      await this.programs.Traction.account.optionMarket.fetchNullable(market);
+    if (!marketData) {
+      return [];
+    }
+    const keys = await Promise.all(
+      Array.from(
+        { length: marketData.numSeries.toNumber() },
+        async (_, index) =>
+          (
+            await findOptionSeriesAddress({ market, index })
+          )[0]
+      )
+    );
+    const series =
+      await this.programs.Traction.account.optionSeries.fetchMultiple(keys);
+    // series of closed contracts no longer exist
+    return keys.flatMap((key, i) => {
+      const data = series[i];
+      return data ? [{ key, data: data as OptionSeriesData }] : [];
+    });
+  }
+
   loadContract({
     strike,
     expiryTs,
     direction,
+    exerciseSchedule = AMERICAN_EXERCISE_SCHEDULE,
+    exerciseWindowSecs = 0,
   }: {
     strike: Price;
     /**
@@ -74,9 +185,21 @@ export class TractionSDK {
      */
     expiryTs: number;
     direction: "put" | "call";
+    exerciseSchedule?: ExerciseSchedule;
+    /**
+     * Number of seconds after expiry that options may still be exercised.
+     */
+    exerciseWindowSecs?: number;
   }): OptionsContract {
     const isPut = direction === "put";
-    return new OptionsContract(this, strike, expiryTs, isPut);
+    return new OptionsContract(
+      this,
+      strike,
+      expiryTs,
+      isPut,
+      exerciseSchedule,
+      exerciseWindowSecs
+    );
   }
 
   async loadContractFromKey({
@@ -122,12 +245,23 @@ export class TractionSDK {
       const quoteMintParsed = deserializeMint(quoteMintRaw.accountInfo.data);
       quote = Token.fromMint(contractData.quoteMint, quoteMintParsed.decimals);
     }
-    const strike = new Price(underlying, quote, 10 ** 9, contractData.strike);
+    const strike = new Price(
+      underlying,
+      quote,
+      contractData.strikeUnits,
+      contractData.strike
+    );
     return new OptionsContract(
       this,
       strike,
       contractData.expiryTs.toNumber(),
-      !!contractData.isPut
+      !!contractData.isPut,
+      contractData.exerciseSchedule as ExerciseSchedule,
+      contractData.exerciseWindowSecs,
+      !!contractData.isCashSettled,
+      contractData.oracle,
+      contractData.creatorFeeKbps.toNumber(),
+      contractData.creatorFeeTo
     );
   }
 
@@ -142,6 +276,13 @@ export class TractionSDK {
     strike,
     expiryTs,
     direction,
+    exerciseSchedule = AMERICAN_EXERCISE_SCHEDULE,
+    exerciseWindowSecs = 0,
+    isCashSettled = false,
+    oracle = SystemProgram.programId,
+    creatorFeeKBPS = 0,
+    creatorFeeTo = creatorFeeKBPS > 0 ? payer : SystemProgram.programId,
+    creator = this.provider.wallet.publicKey,
   }: {
     payer?: PublicKey;
     writerMintKP?: Keypair;
@@ -152,14 +293,73 @@ export class TractionSDK {
      */
     expiryTs: number;
     direction: "put" | "call";
+    exerciseSchedule?: ExerciseSchedule;
+    /**
+     * Number of seconds after expiry that options may still be exercised.
+     */
+    exerciseWindowSecs?: number;
+    /**
+     * Whether the options are settled in cash at expiry rather than exercised.
+     */
+    isCashSettled?: boolean;
+    /**
+     * Oracle providing the settlement price of cash-settled options.
+     */
+    oracle?: PublicKey;
+    /**
+     * Creator fee charged on exercise, in thousands of BPS.
+     */
+    creatorFeeKBPS?: number;
+    /**
+     * Owner of the accounts that receive the creator fee.
+     * Defaults to the payer, or to the system program if there is no creator fee.
+     */
+    creatorFeeTo?: PublicKey;
+    /**
+     * Creator of the contract, which must sign.
+     */
+    creator?: PublicKey;
   }): Promise<{
     optionsContract: OptionsContract;
     tx: TransactionEnvelope;
   }> {
     const isPut = direction === "put";
     const underlying = strike.baseCurrency;
-    const optionsContract = new OptionsContract(this, strike, expiryTs, isPut);
+    const optionsContract = new OptionsContract(
+      this,
+      strike,
+      expiryTs,
+      isPut,
+      exerciseSchedule,
+      exerciseWindowSecs,
+      isCashSettled,
+      oracle,
+      creatorFeeKBPS,
+      creatorFeeTo
+    );
     const [contractKey, contractBump] = await optionsContract.findAddress();
+    const [protocolConfig] = await findProtocolConfigAddress();
+
+    // the contract is appended to the market of its pair, which is created if needed
+    const underlyingMint = underlying.mintAccount;
+    const quoteMint = optionsContract.quote.mintAccount;
+    const [market] = await findOptionMarketAddress({
+      underlyingMint,
+      quoteMint,
+    });
+    const marketData =
+      await this.programs.Traction.account.optionMarket.fetchNullable(market);
+    const newMarketTX = marketData
+      ? null
+      : await this.newOptionMarket({ underlyingMint, quoteMint, payer });
+    const [series, seriesBump] = await findOptionSeriesAddress({
+      market,
+      index: marketData ? marketData.numSeries.toNumber() : 0,
+    });
+    const [marketParams] = await findMarketParamsAddress({
+      underlyingMint,
+      quoteMint,
+    });
     const { instructions: createAccountInstructions } = await getOrCreateATAs({
       provider: this.provider,
       mints: {
@@ -191,8 +391,14 @@ export class TractionSDK {
       optionsContract.rawStrike,
       new u64(expiryTs),
       isPut,
+      exerciseSchedule,
+      isCashSettled,
       contractBump,
       crateBump,
+      exerciseWindowSecs,
+      new u64(creatorFeeKBPS),
+      creatorFeeTo,
+      seriesBump,
       {
         accounts: {
           contract: contractKey,
@@ -200,6 +406,12 @@ export class TractionSDK {
           underlyingMint: underlying.mintAccount,
           quoteMint: optionsContract.quote.mintAccount,
           optionMint: optionMintKP.publicKey,
+          oracle,
+          protocolConfig,
+          market,
+          series,
+          marketParams,
+          creator,
           writerCrate: {
             crateMint: writerMintKP.publicKey,
             crateToken,
@@ -229,6 +441,7 @@ export class TractionSDK {
         ...createWriterMint.instructions,
         ...createOptionMint.instructions,
         ...feeATAs.instructions,
+        ...(newMarketTX?.instructions ?? []),
         newContractIx,
       ],
       [writerMintKP, optionMintKP]
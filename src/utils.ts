@@ -1,2 +1,26 @@
+import type { ExerciseSchedule } from "./programs/traction";
+
 export const dateToTimestamp = (date: Date): number =>
   Math.floor(date.getTime() / 1_000);
+
+/**
+ * An {@link ExerciseSchedule} for options that can be exercised at any time before expiry.
+ */
+export const AMERICAN_EXERCISE_SCHEDULE: ExerciseSchedule = {
+  style: { american: {} },
+  windowSecs: 0,
+  periodSecs: 0,
+};
+
+/**
+ * Encodes an {@link ExerciseSchedule} into the bytes used as a PDA seed.
+ */
+export const encodeExerciseSchedule = (schedule: ExerciseSchedule): Buffer => {
+  const seed = Buffer.alloc(9);
+  const style =
+    "american" in schedule.style ? 0 : "european" in schedule.style ? 1 : 2;
+  seed.writeUInt8(style, 0);
+  seed.writeUInt32LE(schedule.windowSecs, 1);
+  seed.writeUInt32LE(schedule.periodSecs, 5);
+  return seed;
+};
@@ -31,6 +31,14 @@ describe("Traction options", () => {
   let provider: Provider;
   let sdk: TractionSDK;
 
+  before("Create protocol config", async () => {
+    const sdk = makeSDK();
+    await expectTX(
+      await sdk.newProtocolConfig({ treasury: sdk.provider.wallet.publicKey }),
+      "new protocol config"
+    ).to.be.fulfilled;
+  });
+
   beforeEach("Initialize", () => {
     sdk = makeSDK();
     provider = sdk.provider;